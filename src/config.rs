@@ -13,11 +13,15 @@ use toml;
 use crate::{
     constants::{
         APP_NAME, CONFIG_FILE_NAME, DASHBOARD_DEVICE_INTERNAL_PROFILE, DEFAULT_BAUD_RATE,
-        DEFAULT_DEVICE_NAME,
+        DEFAULT_DEVICE_NAME, DEFAULT_HTTP_API_ADDRESS, DEFAULT_MQTT_PORT, TCP_SERVER_ADDR,
     },
     log_error, log_info,
-    service::interaction::InteractionKind,
-    tcp::{client_to_server_message, get_server_data},
+    service::{
+        bus::{self, ConfigEvent, ServiceEvent},
+        interaction::{InteractionKind, TriggerOptions},
+        schedule::validate_schedule,
+    },
+    tcp::client_to_server_message,
     utility::get_app_directory,
 };
 
@@ -44,17 +48,104 @@ pub struct Settings {
     // Serial
     pub port_name: String,
     pub baud_rate: u32,
+
+    // MQTT
+    #[serde(default)]
+    pub mqtt: MqttSettings,
+
+    // Automatic profile switching
+    #[serde(default)]
+    pub focus_profiles: FocusProfileSettings,
+
+    // IPC
+    #[serde(default)]
+    pub ipc: IpcSettings,
+
+    // HTTP API
+    #[serde(default)]
+    pub http_api: HttpApiSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IpcSettings {
+    pub unix_socket: bool, // Linux only, needs `$XDG_RUNTIME_DIR`
+    pub tcp_address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpApiSettings {
+    pub enabled: bool,
+    pub address: String, // Only this computer can connect to `127.0.0.1`
+    pub token: String,   // Required by every request, the IPC token is used if it's empty
+    pub allowed_origins: Vec<String>, // Websites that can use the API, besides localhost
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: String,
+    pub password: String,
+    pub subscriptions: Vec<MqttSubscription>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttSubscription {
+    pub topic: String, // Supports MQTT wildcards. e.g. `home/+/state` or `lab/#`
+    pub action: MqttSubscriptionAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MqttSubscriptionAction {
+    Led(u8 /* id */), // Payload: `on`/`off` or a brightness value (0-255)
+    Display,          // Payload: text to show on the device's display
+    Profile,          // Payload: profile name or index
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusProfileSettings {
+    pub enabled: bool,
+    pub fallback: String, // Profile name used when no rule matches, empty keeps the current one
+    pub rules: Vec<FocusProfileRule>, // First matching rule wins
+}
+
+/// Empty fields match any window, both fields are matched case-insensitively as substrings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusProfileRule {
+    pub profile: String, // Profile name or index
+    #[serde(default)]
+    pub class: String, // Window class. e.g. `firefox`, `code`
+    #[serde(default)]
+    pub title: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Profile {
     pub name: String,
+    // Times when the `Service` switches to this profile automatically
+    #[serde(default)]
+    pub schedules: Vec<ProfileSchedule>,
     pub interactions: HashMap<
         String, /* component_global_id: component's key in `components` inside `Layout` */
         Interaction,
     >,
 }
 
+/// Ranges that end before they start continue past midnight. e.g. `22:00` to `02:00`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileSchedule {
+    #[serde(default)]
+    pub days: Vec<String>, // e.g. `mon`, `tuesday`, `weekdays`, `weekend`, empty means every day
+    pub start: String, // `HH:MM`
+    pub end: String,   // `HH:MM`
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Layout {
     pub name: String,
@@ -66,6 +157,8 @@ pub struct Layout {
 pub struct Interaction {
     pub normal: InteractionKind,
     pub modkey: InteractionKind,
+    #[serde(default)]
+    pub trigger: TriggerOptions,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -76,7 +169,7 @@ pub struct Component {
     pub style: u8,
 }
 
-#[derive(Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ComponentKind {
     None,
     Button,
@@ -125,11 +218,16 @@ impl Default for Config {
                 device_name: DEFAULT_DEVICE_NAME.to_string(),
                 port_name: String::new(),
                 baud_rate: DEFAULT_BAUD_RATE,
+                mqtt: MqttSettings::default(),
+                focus_profiles: FocusProfileSettings::default(),
+                ipc: IpcSettings::default(),
+                http_api: HttpApiSettings::default(),
             },
             profiles: vec![
                 // Device's internal profile
                 Profile {
                     name: DASHBOARD_DEVICE_INTERNAL_PROFILE.to_string(),
+                    schedules: vec![],
                     interactions: Default::default(),
                 },
             ],
@@ -138,6 +236,40 @@ impl Default for Config {
     }
 }
 
+impl Default for IpcSettings {
+    fn default() -> Self {
+        Self {
+            unix_socket: true,
+            tcp_address: TCP_SERVER_ADDR.to_string(),
+        }
+    }
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: DEFAULT_HTTP_API_ADDRESS.to_string(),
+            token: String::new(),
+            allowed_origins: vec![],
+        }
+    }
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: DEFAULT_MQTT_PORT,
+            client_id: APP_NAME.to_lowercase(),
+            username: String::new(),
+            password: String::new(),
+            subscriptions: vec![],
+        }
+    }
+}
+
 impl Default for Layout {
     fn default() -> Self {
         Self {
@@ -153,6 +285,7 @@ impl Default for Interaction {
         Self {
             normal: InteractionKind::None(),
             modkey: InteractionKind::None(),
+            trigger: TriggerOptions::default(),
         }
     }
 }
@@ -188,10 +321,8 @@ impl Config {
         }
     }
 
-    pub fn read(&self) -> Result<Config, Box<dyn Error>> {
-        let mut file_path = &self.file_path;
-
-        // Look for a config file inside application's folder
+    /// A config file inside the application's folder is used instead of `file_path`
+    pub fn find_file_path(&self) -> String {
         let app_path = std::env::current_exe().unwrap();
         let app_folder = std::path::Path::new(&app_path).parent().unwrap();
         let config_file = format!(
@@ -201,9 +332,17 @@ impl Config {
         );
 
         if Path::new(&config_file).exists() {
-            log_info!("A config file was found in the application's folder and will be used...");
+            config_file
+        } else {
+            self.file_path.clone()
+        }
+    }
 
-            file_path = &config_file;
+    pub fn read(&self) -> Result<Config, Box<dyn Error>> {
+        let file_path = &self.find_file_path();
+
+        if *file_path != self.file_path {
+            log_info!("A config file was found in the application's folder and will be used...");
         }
 
         // Couldn't find any config file in the application's folder
@@ -267,6 +406,24 @@ impl Config {
         }
     }
 
+    /// Find a profile by its name or its index
+    pub fn find_profile(&self, name_or_index: &str) -> Option<usize> {
+        let name_or_index = name_or_index.trim();
+
+        if let Some(index) = self
+            .profiles
+            .iter()
+            .position(|profile| profile.name == name_or_index)
+        {
+            return Some(index);
+        }
+
+        match name_or_index.parse::<usize>() {
+            Ok(index) if index < self.profiles.len() => Some(index),
+            _ => None,
+        }
+    }
+
     pub fn does_profile_exist(&self, profile_name: &String) -> bool {
         for profile in &self.profiles {
             if profile.name == *profile_name {
@@ -431,6 +588,58 @@ pub fn init() -> bool {
     true
 }
 
+/// Parse the content of a config file and check its profiles, every problem is returned
+pub fn check_config(toml_str: &str) -> Result<Config, Vec<String>> {
+    let config: Config = toml::from_str(toml_str).map_err(|e| vec![e.to_string()])?;
+
+    let mut errors = vec![];
+
+    if config.settings.current_profile >= config.profiles.len() {
+        errors.push(format!(
+            "`current_profile` is {}, but there are only {} profile(s)",
+            config.settings.current_profile,
+            config.profiles.len()
+        ));
+    }
+
+    for profile in &config.profiles {
+        for schedule in &profile.schedules {
+            if let Err(e) = validate_schedule(schedule) {
+                errors.push(format!(
+                    "Profile `{}`: Invalid schedule: {}",
+                    profile.name, e
+                ));
+            }
+        }
+
+        let mut component_global_ids: Vec<&String> = profile.interactions.keys().collect();
+
+        component_global_ids.sort();
+
+        for component_global_id in component_global_ids {
+            let interaction = &profile.interactions[component_global_id];
+
+            for (name, kind) in [
+                ("normal", &interaction.normal),
+                ("modkey", &interaction.modkey),
+            ] {
+                if let Err(e) = kind.validate() {
+                    errors.push(format!(
+                        "Profile `{}`, `{}` ({}): {}",
+                        profile.name, component_global_id, name, e
+                    ));
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(config)
+}
+
 // Function for applying changes to config and send a message to `TCP clients` to reload it
 pub fn update_config_and_client<F>(config: &mut Config, callback: F)
 where
@@ -438,13 +647,7 @@ where
 {
     config.save(callback, true);
 
-    if let Ok(mut data) = get_server_data().lock() {
-        let mut server_data = data.clone();
-
-        server_data.order = "reload_config".to_string();
-
-        *data = server_data;
-    }
+    bus::publish(ServiceEvent::Config(ConfigEvent::Changed()));
 }
 
 // Function for applying changes to config and send a message to `TCP server` to reload it
@@ -454,5 +657,5 @@ where
 {
     config.save(callback, true);
 
-    client_to_server_message("reload_config").ok();
+    client_to_server_message("reload_config", serde_json::Value::Null).ok();
 }
@@ -15,20 +15,75 @@ pub const DEFAULT_BAUD_RATE: u32 = 38_400;
 pub const SERIAL_MESSAGE_SEP: &str = ":";
 pub const SERIAL_MESSAGE_INNER_SEP: &str = "|";
 pub const SERIAL_MESSAGE_END: &str = ";";
+pub const SERIAL_READ_TIMEOUT: u64 = 100; // Waiting duration for incoming data (in ms)
 
 // TCP
-pub const TCP_SERVER_ADDR: &str = "127.0.0.1:51690"; // Random number in range of unused ports
+pub const TCP_SERVER_ADDR: &str = "127.0.0.1:51690"; // Default, random number in range of unused ports
 pub const TCP_READ_TIMEOUT: u64 = 5000; // Client waiting duration for server response (in ms)
-pub const TCP_BUFFER_SIZE: usize = 4096; // 4 KB buffer
-pub const SERVER_DATA_UPDATE_INTERVAL: u64 = 16; // If you don't care about the `Dashboard` being
-                                                 // responsive while device components are being used
-                                                 // you can have higher interval (in ms) (16 ≈ 60FPS)
-                                                 // if client isn't connected, interval is 1000ms
+pub const IPC_PROTOCOL_VERSION: u32 = 1; // Increased when a message format changes
+pub const TCP_RECONNECT_INTERVAL: u64 = 1000; // Waiting duration before subscribing again (in ms)
+pub const IPC_TOKEN_FILE_NAME: &str = "ipc_token"; // Secret that clients need to authenticate
+pub const IPC_ENDPOINT_FILE_NAME: &str = "ipc_endpoint"; // Where the `Service` is listening
+pub const IPC_SOCKET_FILE_NAME: &str = "padpad.sock"; // Created in `$XDG_RUNTIME_DIR`
+pub const IPC_PING_TIMEOUT: u64 = 1000; // Waiting duration for the identity check (in ms)
+pub const IPC_AUTHENTICATION_TIMEOUT: u64 = 5000; // Unauthenticated clients are disconnected after it (in ms)
+pub const IPC_MAX_UNAUTHENTICATED_MESSAGE_SIZE: usize = 65_536; // 64 KB, before `authenticate`
+pub const IPC_MAX_MESSAGE_SIZE: usize = 16_777_216; // 16 MB, e.g. `config_import`
+pub const IPC_SUBSCRIBER_QUEUE_SIZE: usize = 1024; // Subscribers are dropped when this many events wait
+pub const IPC_SUBSCRIBER_WRITE_TIMEOUT: u64 = 5000; // Subscribers that stop reading are dropped (in ms)
+
+// Single instance
+pub const INSTANCE_LOCK_FILE_NAME: &str = "padpad.lock"; // Holds the PID of the running `Service`
+pub const INSTANCE_LOCK_TIMEOUT: u64 = 5000; // Waiting duration for the old `Service` on restart (in ms)
+pub const INSTANCE_RESTART_ENV_VAR: &str = "PADPAD_RESTARTED";
+
+// Shutdown
+pub const SHUTDOWN_TIMEOUT: u64 = 3000; // Waiting duration for the threads to stop (in ms)
+pub const SERVER_ACCEPT_INTERVAL: u64 = 50; // Servers check for new connections and shutdown (in ms)
+
+// Command
+pub const COMMAND_POLL_INTERVAL: u64 = 50; // Interval of checking running commands (in ms)
+pub const COMMAND_OUTPUT_MAX_LENGTH: usize = 2048; // Only the end of the output is kept (in bytes)
+
+// Clipboard
+pub const CLIPBOARD_HISTORY_SIZE: usize = 20; // Max number of entries in the history
+pub const CLIPBOARD_POLL_INTERVAL: u64 = 1000; // Interval of checking for copied text (in ms)
+pub const CLIPBOARD_RESTORE_DELAY: u64 = 300; // Waiting duration before restoring after paste (in ms)
+
+// Automatic profile switching
+pub const FOCUS_POLL_INTERVAL: u64 = 500; // Interval of checking the focused window (in ms)
+pub const SCHEDULE_POLL_INTERVAL: u64 = 5000; // Interval of checking profile schedules (in ms)
+
+// Script
+pub const SCRIPT_TIMEOUT: u64 = 5000; // Scripts are stopped after this duration (in ms)
+pub const SCRIPT_MAX_CALL_LEVELS: usize = 64; // Limits recursion of script functions
+pub const SCRIPT_MAX_COLLECTION_SIZE: usize = 65_536; // Max size of strings, arrays and maps
+pub const SCRIPT_VARIABLES_FILE_NAME: &str = "script_variables.json";
+
+// Plugins
+pub const PLUGINS_DIRECTORY_NAME: &str = "plugins"; // Next to the config file
+pub const PLUGIN_TIMEOUT: u64 = 5000; // Waiting duration for a plugin's `describe` (in ms)
+
+// HTTP API
+pub const DEFAULT_HTTP_API_ADDRESS: &str = "127.0.0.1:51691"; // Localhost only by default
+pub const HTTP_API_READ_TIMEOUT: u64 = 5000; // Waiting duration for a request (in ms)
+pub const HTTP_API_MAX_BODY_SIZE: usize = 1_048_576; // 1 MB
+pub const HTTP_API_MAX_LINE_LENGTH: usize = 8192; // For the request line and each header
+pub const HTTP_API_MAX_HEADERS: usize = 100;
+pub const HTTP_API_MAX_CONNECTIONS: usize = 64; // Including WebSockets, new ones are closed above it
+pub const WEBSOCKET_PING_INTERVAL: u64 = 30_000; // Detects closed WebSockets (in ms)
+
+// MQTT
+pub const DEFAULT_MQTT_PORT: u16 = 1883;
+pub const MQTT_KEEP_ALIVE: u64 = 30; // Interval of pings to the broker (in seconds)
+pub const MQTT_RECONNECT_INTERVAL: u64 = 5000; // Waiting duration before reconnecting (in ms)
 
 // Dashboard
 pub const DASHBOARD_DISAPLY_PIXEL_SIZE: f32 = 3.28125;
+pub const DASHBOARD_SIMULATE_POTENTIOMETER_STEP: i16 = 5; // Value change per scroll in simulate mode
 pub const DASHBOARD_DEVICE_INTERNAL_PROFILE: &str = "Internal";
 pub const DASHBOARD_PROFILE_MAX_CHARACTERS: usize = 10;
+pub const DASHBOARD_MAX_SERVER_EVENTS: usize = 1024; // Events kept until the next frame
 
 pub const HOME_IMAGE_WIDTH: usize = 42;
 pub const HOME_IMAGE_HEIGHT: usize = 42;
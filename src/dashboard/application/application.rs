@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     sync::{Arc, Mutex, OnceLock},
 };
 
@@ -8,8 +8,8 @@ use eframe::egui::{self, Context, DragValue, Pos2, Rect, Response, Ui, Vec2};
 use super::{
     get_current_style,
     utility::{
-        blend_colors, request_device_upload, request_refresh_device, request_restart_service,
-        request_send_serial,
+        blend_colors, request_command_output, request_device_upload, request_plugins,
+        request_refresh_device, request_restart_service, request_send_serial, request_simulate,
     },
     widgets::*,
 };
@@ -19,20 +19,28 @@ use padpad_software::{
     },
     constants::{
         APP_MIN_HEIGHT, APP_MIN_WIDTH, APP_NAME, APP_PADDING_X, APP_PADDING_Y, APP_VERSION,
-        DASHBOARD_DISAPLY_PIXEL_SIZE, DASHBOARD_PROFILE_MAX_CHARACTERS, DEFAULT_BAUD_RATE,
+        DASHBOARD_DISAPLY_PIXEL_SIZE, DASHBOARD_MAX_SERVER_EVENTS,
+        DASHBOARD_PROFILE_MAX_CHARACTERS, DASHBOARD_SIMULATE_POTENTIOMETER_STEP, DEFAULT_BAUD_RATE,
         DEFAULT_DEVICE_NAME, FORBIDDEN_CHARACTERS, HOME_IMAGE_BYTES_SIZE, HOME_IMAGE_DEFAULT_BYTES,
         HOME_IMAGE_HEIGHT, HOME_IMAGE_WIDTH, KEYS, SERIAL_MESSAGE_END, SERIAL_MESSAGE_INNER_SEP,
-        SERIAL_MESSAGE_SEP, SERVER_DATA_UPDATE_INTERVAL,
+        SERIAL_MESSAGE_SEP, TCP_RECONNECT_INTERVAL,
     },
     log_error,
-    service::interaction::InteractionKind,
-    tcp::{client_to_server_message, ServerData},
+    service::interaction::{
+        ClipboardAction, CommandOptions, CommandOutput, Comparison, Condition, Confirmation,
+        DBusArgument, DBusCall, HttpRequest, HttpResponseAction, InteractionKind, KeyStep,
+        MediaAction, PluginCall, TriggerOptions,
+    },
+    service::plugin::{PluginDescription, PluginFieldKind},
+    tcp::{subscribe_to_server, ServerData, ServerEvent},
     utility::{extract_hex_bytes, hex_bytes_string_to_vec, hex_bytes_vec_to_string, restart},
 };
 
 static SERVER_DATA: OnceLock<Arc<Mutex<ServerData>>> = OnceLock::new();
 static ERROR_MESSAGE: OnceLock<Arc<Mutex<String>>> = OnceLock::new(); // Global vairable to keep the
                                                                       // last unavoidable error message
+                                                                      // Received events since last frame, at most `DASHBOARD_MAX_SERVER_EVENTS`
+static SERVER_EVENTS: OnceLock<Arc<Mutex<VecDeque<ServerEvent>>>> = OnceLock::new();
 
 pub struct Application {
     update_available: bool,
@@ -61,6 +69,8 @@ pub struct Application {
         ((u8, String), (u8, String), bool), /* (normal (byte, str), mod (byte, str), is_modkey) */
     >,
     is_editing_layout: bool,
+    is_simulating: bool, // Components run their interactions as if they were used on the device
+    simulated_button: Option<String /* component_global_id */>, // Held down in simulate mode
     dragged_component_offset: (f32, f32),
     layout_grid: (bool /* enabled/disabled */, f32 /* size */),
     /// For storing last components state before editing layout
@@ -127,7 +137,7 @@ impl eframe::App for Application {
         }
 
         // Server data
-        self.handle_server_data();
+        self.handle_server_data(ctx);
 
         // Modal manager
         self.handle_modal(ctx);
@@ -293,7 +303,7 @@ impl Application {
         ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
     }
 
-    fn handle_server_data(&mut self) {
+    fn handle_server_data(&mut self, ctx: &Context) {
         // Access the latest server data
         if let Some(server_data) = SERVER_DATA.get() {
             let new_server_data = server_data.lock().unwrap().clone();
@@ -312,50 +322,33 @@ impl Application {
             }
         }
 
-        // Handle server orders
-        if !self.server_data.order.is_empty() {
-            let mut handled = false;
-
-            match self.server_data.order.as_str() {
-                "reload_config" => {
-                    handled = true;
+        // Handle server events in the order they happened
+        let events = match SERVER_EVENTS.get() {
+            Some(events) => std::mem::take(&mut *events.lock().unwrap()),
+            None => VecDeque::new(),
+        };
 
+        for event in events {
+            match event {
+                ServerEvent::Component(component_global_id, value) => {
+                    self.components.insert(component_global_id, value);
+                }
+                ServerEvent::ReloadConfig() => {
                     if let Some(config) = &mut self.config {
                         config.load();
                     }
                 }
-                _ => (),
-            }
-
-            if handled {
-                if let Some(server_data) = SERVER_DATA.get() {
-                    let mut new_server_data = server_data.lock().unwrap();
-
-                    self.server_data.order = String::new();
-
-                    *new_server_data = self.server_data.clone();
+                // Replaces the output that's shown under the command's options
+                ServerEvent::CommandOutput(component_global_id, output) => {
+                    ctx.data_mut(|data| {
+                        data.insert_temp(
+                            command_output_id(&component_global_id),
+                            Ok::<CommandOutput, String>(output),
+                        )
+                    });
                 }
-
-                client_to_server_message("handled").ok();
-            }
-        }
-
-        // Update component values
-        if !self.server_data.last_updated_component.0.is_empty() {
-            let component_global_id = self.server_data.last_updated_component.0.clone();
-            let value = self.server_data.last_updated_component.1.clone();
-
-            *self
-                .components
-                .entry(component_global_id)
-                .or_insert(String::new()) = value;
-
-            if let Some(server_data) = SERVER_DATA.get() {
-                let mut new_server_data = server_data.lock().unwrap();
-
-                self.server_data.last_updated_component = (String::new(), String::new());
-
-                *new_server_data = self.server_data.clone();
+                // The config is reloaded by `ReloadConfig`, `State` is stored in `SERVER_DATA`
+                ServerEvent::Profile(_, _) | ServerEvent::State(_) => (),
             }
         }
     }
@@ -978,6 +971,18 @@ impl Application {
                     };
 
                     if !self.is_editing_layout {
+                        if self.is_simulating {
+                            self.simulate_component_event(
+                                ui,
+                                &component.0,
+                                &kind,
+                                &value,
+                                &response,
+                            );
+
+                            continue;
+                        }
+
                         if response.double_clicked() {
                             self.toggle_layout_state();
 
@@ -1108,6 +1113,61 @@ impl Application {
         }
     }
 
+    /// Toggle simulate mode, a button that's still held is released
+    fn toggle_simulate_mode(&mut self) {
+        self.is_simulating = !self.is_simulating;
+
+        if let Some(component_global_id) = self.simulated_button.take() {
+            request_simulate(&component_global_id, "0", false).ok();
+        }
+    }
+
+    /// Buttons are pressed by clicking (hold `Shift` for `ModKey`) and potentiometers are turned by
+    /// scrolling, the `Service` handles them like events from the device
+    fn simulate_component_event(
+        &mut self,
+        ui: &Ui,
+        component_global_id: &str,
+        kind: &ComponentKind,
+        value: &str,
+        response: &Response,
+    ) {
+        let modkey = ui.input(|i| i.modifiers.shift);
+
+        let value = match kind {
+            ComponentKind::Button => {
+                let is_down = response.is_pointer_button_down_on();
+                let was_down = self.simulated_button.as_deref() == Some(component_global_id);
+
+                if is_down == was_down {
+                    return;
+                }
+
+                self.simulated_button = is_down.then(|| component_global_id.to_string());
+
+                if is_down { "1" } else { "0" }.to_string()
+            }
+            ComponentKind::Potentiometer if response.hovered() => {
+                let scroll = ui.input(|i| i.raw_scroll_delta.y);
+
+                if scroll == 0.0 {
+                    return;
+                }
+
+                let step = DASHBOARD_SIMULATE_POTENTIOMETER_STEP * scroll.signum() as i16;
+
+                (value.parse::<i16>().unwrap_or(0) + step)
+                    .clamp(0, 99)
+                    .to_string()
+            }
+            _ => return,
+        };
+
+        if let Err(e) = request_simulate(component_global_id, &value, modkey) {
+            log_error!("Could not simulate `{}`: {}", component_global_id, e);
+        }
+    }
+
     /// Toggle layout state between editing and viewing
     fn toggle_layout_state(&mut self) {
         self.is_editing_layout = !self.is_editing_layout;
@@ -1689,6 +1749,7 @@ impl Application {
 
             let new_profile = Profile {
                 name: name.trim().to_string(),
+                schedules: vec![],
                 interactions: {
                     let mut interactions: HashMap<String, Interaction> = Default::default();
 
@@ -2265,7 +2326,7 @@ impl Application {
         let panel_position_x =
             animate_value(ui.ctx(), "toolbar-panel-position", self.toolbar_panel, 0.25);
 
-        let buttons_count = 5; // 4 buttons + extra spacing
+        let buttons_count = 6; // 5 buttons + extra spacing
 
         let padding = ui.style().spacing.item_spacing.x - 2.0;
         let button_size = vec2(42.0, 42.0);
@@ -2346,6 +2407,23 @@ impl Application {
                                 self.open_import_export_modal();
                             }
 
+                            if ui
+                                .add_sized(
+                                    button_size,
+                                    Button::new(RichText::new("▶").size(24.0))
+                                        .selected(self.is_simulating),
+                                )
+                                .on_hover_text(
+                                    "Simulate mode\n\
+                                    Click buttons (hold Shift for ModKey) and scroll over\n\
+                                    potentiometers to run them without the device",
+                                )
+                                .on_hover_cursor(CursorIcon::PointingHand)
+                                .clicked()
+                            {
+                                self.toggle_simulate_mode();
+                            }
+
                             ui.separator();
 
                             if ui
@@ -3080,8 +3158,6 @@ impl Application {
                     }
                 ));
 
-                ui.label(format!("Server current order: {}", self.server_data.order));
-
                 ui.label(format!("Current profile: {}", current_profile));
 
                 // Raw components layout
@@ -4684,14 +4760,14 @@ impl Application {
 
             if let Some(interactions) = current_interactions {
                 match &interactions.normal {
-                    InteractionKind::Shortcut(_keys, text) => {
+                    InteractionKind::Shortcut(_keys, text, _hold) => {
                         self.properties_shortcut_kind.0 = text.is_empty();
                     }
                     _ => (),
                 }
 
                 match &interactions.modkey {
-                    InteractionKind::Shortcut(_keys, text) => {
+                    InteractionKind::Shortcut(_keys, text, _hold) => {
                         self.properties_shortcut_kind.1 = text.is_empty();
                     }
                     _ => (),
@@ -5280,12 +5356,16 @@ impl Application {
                         draw_normal_interaction_panel(
                             ui,
                             interactions,
-                            has_value,
+                            InteractionEditor {
+                                component_global_id: &component_global_id,
+                                has_value,
+                                properties_shortcut_key_filter: &mut app
+                                    .properties_shortcut_key_filter,
+                                should_update: &mut should_update_interactions,
+                            },
                             hid_warning_normal_condition,
                             &mut app.properties_shortcut_kind,
-                            &mut app.properties_shortcut_key_filter,
                             &mut should_open_button_memory_manager,
-                            &mut should_update_interactions,
                         );
                     } else {
                         // Check if this component supports modkey interaction
@@ -5293,17 +5373,45 @@ impl Application {
                             draw_modkey_interaction_panel(
                                 ui,
                                 interactions,
-                                has_value,
+                                InteractionEditor {
+                                    component_global_id: &component_global_id,
+                                    has_value,
+                                    properties_shortcut_key_filter: &mut app
+                                        .properties_shortcut_key_filter,
+                                    should_update: &mut should_update_interactions,
+                                },
                                 hid_warning_modkey_condition,
                                 &mut app.properties_shortcut_kind,
-                                &mut app.properties_shortcut_key_filter,
                                 &mut should_open_button_memory_manager,
-                                &mut should_update_interactions,
                             );
                         }
                     }
 
-                    if should_update_interactions {
+                    ui.add_space(ui.style().spacing.item_spacing.y);
+
+                    draw_trigger_options_fields(
+                        ui,
+                        &mut interactions.trigger,
+                        kind == ComponentKind::Button,
+                        &mut should_update_interactions,
+                    );
+
+                    // Invalid templates/scripts are never written to the config
+                    let validation_error = interactions
+                        .normal
+                        .validate()
+                        .and(interactions.modkey.validate())
+                        .err();
+
+                    if let Some(e) = &validation_error {
+                        ui.vertical_centered_justified(|ui| {
+                            ui.group(|ui| {
+                                ui.label(egui::RichText::new(e).color(Color::RED));
+                            });
+                        });
+                    }
+
+                    if should_update_interactions && validation_error.is_none() {
                         update_component_interactions(
                             &component_global_id,
                             interactions,
@@ -6273,10 +6381,37 @@ impl Application {
     }
 }
 
+/// Events wait here until the next frame, which doesn't come while the window is minimized
+fn queue_server_event(events: &mut VecDeque<ServerEvent>, event: ServerEvent) {
+    let is_redundant = match &event {
+        // The config is reloaded by `ReloadConfig`
+        ServerEvent::Profile(_, _) => true,
+        // One reload covers every change before it
+        ServerEvent::ReloadConfig() => events
+            .iter()
+            .any(|event| matches!(event, ServerEvent::ReloadConfig())),
+        _ => false,
+    };
+
+    if is_redundant {
+        return;
+    }
+
+    // The oldest event is dropped, except a pending reload
+    if events.len() >= DASHBOARD_MAX_SERVER_EVENTS {
+        if let Some(index) = events
+            .iter()
+            .position(|event| !matches!(event, ServerEvent::ReloadConfig()))
+        {
+            events.remove(index);
+        }
+    }
+
+    events.push_back(event);
+}
+
 impl Default for Application {
     fn default() -> Self {
-        let mut first_time = true;
-
         let server_response = SERVER_DATA
             .get_or_init(|| Arc::new(Mutex::new(ServerData::default())))
             .clone();
@@ -6285,6 +6420,10 @@ impl Default for Application {
             .get_or_init(|| Arc::new(Mutex::new(String::new())))
             .clone();
 
+        let server_events = SERVER_EVENTS
+            .get_or_init(|| Arc::new(Mutex::new(VecDeque::new())))
+            .clone();
+
         // IPC handling between dashboard and service self
         std::thread::Builder::new()
             .name("TCP client".to_string())
@@ -6306,47 +6445,32 @@ impl Default for Application {
                     }
                 };
 
-                let mut server_data: Option<ServerData>;
-                let mut data_message = "force_data".to_string();
-
                 loop {
-                    let mut update_interval = SERVER_DATA_UPDATE_INTERVAL;
-
-                    match client_to_server_message(&data_message) {
-                        Ok(r) => {
-                            // "0" means the data hasn't been changed since last ping
-                            if r != "0".to_string() {
-                                server_data = Some(ServerData::parse(r));
-                                update_response(&server_data);
-
-                                // Reset error message
-                                if let Ok(mut error) = error_message.lock() {
-                                    *error = String::new();
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            update_interval = 1000;
+                    // Only returns when the connection is lost
+                    let result = subscribe_to_server(|event| match event {
+                        ServerEvent::State(data) => {
+                            update_response(&Some(data));
 
-                            server_data = None;
-                            update_response(&server_data);
-
-                            // Set error message for `error_modal` in `Application`
+                            // Reset error message
                             if let Ok(mut error) = error_message.lock() {
-                                *error = e.clone();
+                                *error = String::new();
                             }
-
-                            log_error!("{}", e.replace('\n', " "));
                         }
-                    }
+                        event => queue_server_event(&mut server_events.lock().unwrap(), event),
+                    });
 
-                    if first_time {
-                        first_time = false;
+                    if let Err(e) = result {
+                        update_response(&None);
 
-                        data_message = "data".to_string();
+                        // Set error message for `error_modal` in `Application`
+                        if let Ok(mut error) = error_message.lock() {
+                            *error = e.to_string();
+                        }
+
+                        log_error!("{}", e.to_string().replace('\n', " "));
                     }
 
-                    std::thread::sleep(std::time::Duration::from_millis(update_interval));
+                    std::thread::sleep(std::time::Duration::from_millis(TCP_RECONNECT_INTERVAL));
                 }
             })
             .expect("Failed to spawn `TCP client` thread!");
@@ -6382,6 +6506,8 @@ impl Default for Application {
             component_properties: (None, None), // Current editing component properties
             button_memory: Default::default(),
             is_editing_layout: false,
+            is_simulating: false,
+            simulated_button: None,
             dragged_component_offset: (0.0, 0.0),
             layout_grid: (true, 10.0),
             components_backup: Default::default(),
@@ -6432,16 +6558,27 @@ impl Default for Application {
 
 // Panels
 
+/// Shared by the interaction panels and the fields inside them
+struct InteractionEditor<'a> {
+    component_global_id: &'a str,
+    // (bool, &str) -> `true/false`, `hint_text`
+    has_value: (bool, &'a str), // does the component have a value? e.g. potentiometer has 0-99
+    properties_shortcut_key_filter: &'a mut String,
+    should_update: &'a mut bool,
+}
+
 fn draw_normal_interaction_panel(
     ui: &mut Ui,
     interactions: &mut Interaction,
-    // (bool, &str) -> `true/false`, `hint_text`
-    has_value: (bool, &str), // does the component have a value? e.g. potentiometer has 0-99
+    InteractionEditor {
+        component_global_id,
+        has_value,
+        properties_shortcut_key_filter,
+        should_update,
+    }: InteractionEditor,
     hid_warning_condition: bool,
     properties_shortcut_kind: &mut (bool, bool), // (normal, modkey) `true` -> keys, `false` -> text
-    properties_shortcut_key_filter: &mut String,
     should_open_button_memory_manager: &mut bool,
-    should_update: &mut bool,
 ) {
     if hid_warning_condition {
         ui.add_space(ui.style().spacing.item_spacing.y);
@@ -6488,14 +6625,47 @@ fn draw_normal_interaction_panel(
         ui.add_space(ui.style().spacing.item_spacing.y * 2.0);
 
         const INTERACTION_NONE: InteractionKind = InteractionKind::None();
-        const INTERACTION_COMMAND: InteractionKind =
-            InteractionKind::Command(String::new(), String::new());
+        const INTERACTION_COMMAND: InteractionKind = InteractionKind::Command(
+            String::new(),
+            String::new(),
+            CommandOptions {
+                use_shell: true,
+                working_directory: String::new(),
+                environment: vec![],
+                timeout: 0,
+                kill_previous: false,
+                display_output: false,
+            },
+        );
         const INTERACTION_APPLICATION: InteractionKind =
             InteractionKind::Application(String::new());
         const INTERACTION_WEBSITE: InteractionKind = InteractionKind::Website(String::new());
         const INTERACTION_SHORTCUT: InteractionKind =
-            InteractionKind::Shortcut(vec![], String::new());
+            InteractionKind::Shortcut(vec![], String::new(), false);
         const INTERACTION_FILE: InteractionKind = InteractionKind::File(String::new());
+        const INTERACTION_KEY_SCRIPT: InteractionKind = InteractionKind::KeyScript(vec![], 1);
+        const INTERACTION_SCRIPT: InteractionKind = InteractionKind::Script(String::new());
+        const INTERACTION_CLIPBOARD: InteractionKind =
+            InteractionKind::Clipboard(ClipboardAction::Copy(String::new()));
+        const INTERACTION_HTTP: InteractionKind = InteractionKind::Http(HttpRequest {
+            method: String::new(),
+            url: String::new(),
+            headers: vec![],
+            body: String::new(),
+            timeout: 0,
+            response: HttpResponseAction::Log,
+        });
+        const INTERACTION_MQTT: InteractionKind =
+            InteractionKind::Mqtt(String::new(), String::new(), false);
+        const INTERACTION_DBUS: InteractionKind = InteractionKind::DBus(DBusCall {
+            destination: String::new(),
+            path: String::new(),
+            interface: String::new(),
+            method: String::new(),
+            arguments: vec![],
+        });
+        const INTERACTION_MEDIA: InteractionKind =
+            InteractionKind::Media(MediaAction::PlayPause, String::new(), false);
 
         egui::ComboBox::new("properties-interactions-normal", "")
             .selected_text(format!("{}", interactions.normal))
@@ -6559,6 +6729,109 @@ fn draw_normal_interaction_panel(
                 {
                     interactions.normal = INTERACTION_FILE;
                 }
+
+                if ui
+                    .selectable_label(
+                        interactions.normal.equals_kind(&INTERACTION_KEY_SCRIPT),
+                        "Key Script",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.normal = INTERACTION_KEY_SCRIPT;
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.normal.equals_kind(&INTERACTION_HTTP),
+                        "HTTP Request",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.normal = InteractionKind::Http(HttpRequest::default());
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.normal.equals_kind(&INTERACTION_MQTT),
+                        "MQTT Publish",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.normal = INTERACTION_MQTT;
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.normal.equals_kind(&INTERACTION_DBUS),
+                        "D-Bus Method",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.normal = INTERACTION_DBUS;
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.normal.equals_kind(&INTERACTION_MEDIA),
+                        "Media Control",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.normal = INTERACTION_MEDIA;
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.normal.equals_kind(&INTERACTION_CLIPBOARD),
+                        "Clipboard",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.normal = INTERACTION_CLIPBOARD;
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.normal.equals_kind(&INTERACTION_SCRIPT),
+                        "Script",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.normal = INTERACTION_SCRIPT;
+                }
+
+                if ui
+                    .selectable_label(
+                        matches!(interactions.normal, InteractionKind::Conditional(_, _, _)),
+                        "Conditional",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.normal = InteractionKind::Conditional(
+                        Condition::Toggle(String::new()),
+                        Box::new(InteractionKind::None()),
+                        Box::new(InteractionKind::None()),
+                    );
+                }
+
+                if ui
+                    .selectable_label(
+                        matches!(interactions.normal, InteractionKind::Plugin(_)),
+                        "Plugin",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.normal = InteractionKind::Plugin(PluginCall::default());
+                }
             })
             .response
             .on_hover_cursor(egui::CursorIcon::PointingHand);
@@ -6607,7 +6880,7 @@ fn draw_normal_interaction_panel(
         InteractionKind::None() => {
             *should_update = true;
         }
-        InteractionKind::Command(command, _shell) => {
+        InteractionKind::Command(command, _shell, options) => {
             ui.horizontal(|ui| {
                 ui.label("Command");
 
@@ -6660,6 +6933,14 @@ fn draw_normal_interaction_panel(
                     *should_update = true;
                 }
             }
+
+            draw_command_options_fields(
+                ui,
+                "properties-interactions-normal-command",
+                component_global_id,
+                options,
+                should_update,
+            );
         }
         InteractionKind::Application(path) => {
             ui.horizontal(|ui| {
@@ -6771,7 +7052,7 @@ fn draw_normal_interaction_panel(
                 }
             }
         }
-        InteractionKind::Shortcut(keys, text) => {
+        InteractionKind::Shortcut(keys, text, hold) => {
             // Keys
             if properties_shortcut_kind.0 {
                 // `text` must be empty in `keys` mode
@@ -6850,8 +7131,37 @@ fn draw_normal_interaction_panel(
                     properties_shortcut_key_filter.clear();
                 }
 
+                // Keys can only be held by components that can be released. e.g. buttons
+                if !has_value.0 {
+                    ui.horizontal(|ui| {
+                        let hold_label_response = ui
+                            .add(egui::Label::new("Hold Keys").sense(egui::Sense::click()))
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text(
+                                "Keys are pressed while the button is held down \n\
+                                and released when the button is released.",
+                            );
+
+                        let hold_switch_response = ui
+                            .add(ToggleSwitch::new(*hold, (50.0, 26.0)))
+                            .on_hover_text(
+                                "Keys are pressed while the button is held down \n\
+                                and released when the button is released.",
+                            );
+
+                        if hold_label_response.clicked() || hold_switch_response.clicked() {
+                            *hold = !*hold;
+
+                            *should_update = true;
+                        }
+                    });
+                }
+
             // Text
             } else {
+                // `hold` is only used in `keys` mode
+                *hold = false;
+
                 ui.label("Text");
 
                 const ROWS: usize = 2;
@@ -6933,19 +7243,112 @@ fn draw_normal_interaction_panel(
                 }
             }
         }
+        InteractionKind::KeyScript(steps, repeat) => {
+            draw_key_script_fields(
+                ui,
+                "properties-interactions-normal-key-script",
+                (steps, repeat),
+                properties_shortcut_key_filter,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Http(request) => {
+            draw_http_request_fields(
+                ui,
+                "properties-interactions-normal-http",
+                request,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Mqtt(topic, payload, retain) => {
+            draw_mqtt_publish_fields(
+                ui,
+                "properties-interactions-normal-mqtt",
+                (topic, payload, retain),
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::DBus(call) => {
+            draw_dbus_call_fields(
+                ui,
+                "properties-interactions-normal-dbus",
+                call,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Clipboard(action) => {
+            draw_clipboard_fields(
+                ui,
+                "properties-interactions-normal-clipboard",
+                action,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Script(script) => {
+            draw_script_fields(
+                ui,
+                "properties-interactions-normal-script",
+                script,
+                should_update,
+            );
+        }
+        InteractionKind::Media(action, player, report_now_playing) => {
+            draw_media_control_fields(
+                ui,
+                "properties-interactions-normal-media",
+                (action, player, report_now_playing),
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Conditional(condition, then, otherwise) => {
+            draw_conditional_fields(
+                ui,
+                "properties-interactions-normal-conditional",
+                InteractionEditor {
+                    component_global_id,
+                    has_value,
+                    properties_shortcut_key_filter,
+                    should_update,
+                },
+                (condition, then, otherwise),
+                default_hint,
+            );
+        }
+        InteractionKind::Plugin(call) => {
+            draw_plugin_fields(
+                ui,
+                "properties-interactions-normal-plugin",
+                call,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
     }
 }
 
 fn draw_modkey_interaction_panel(
     ui: &mut Ui,
     interactions: &mut Interaction,
-    // (bool, &str) -> `true/false`, `hint_text`
-    has_value: (bool, &str), // does the component have a value? e.g. potentiometer has 0-99
+    InteractionEditor {
+        component_global_id,
+        has_value,
+        properties_shortcut_key_filter,
+        should_update,
+    }: InteractionEditor,
     hid_warning_condition: bool,
     properties_shortcut_kind: &mut (bool, bool), // (normal, modkey) `true` -> keys, `false` -> text
-    properties_shortcut_key_filter: &mut String,
     should_open_button_memory_manager: &mut bool,
-    should_update: &mut bool,
 ) {
     if hid_warning_condition {
         ui.add_space(ui.style().spacing.item_spacing.y);
@@ -6992,14 +7395,47 @@ fn draw_modkey_interaction_panel(
         ui.add_space(ui.style().spacing.item_spacing.y * 2.0);
 
         const INTERACTION_NONE: InteractionKind = InteractionKind::None();
-        const INTERACTION_COMMAND: InteractionKind =
-            InteractionKind::Command(String::new(), String::new());
+        const INTERACTION_COMMAND: InteractionKind = InteractionKind::Command(
+            String::new(),
+            String::new(),
+            CommandOptions {
+                use_shell: true,
+                working_directory: String::new(),
+                environment: vec![],
+                timeout: 0,
+                kill_previous: false,
+                display_output: false,
+            },
+        );
         const INTERACTION_APPLICATION: InteractionKind =
             InteractionKind::Application(String::new());
         const INTERACTION_WEBSITE: InteractionKind = InteractionKind::Website(String::new());
         const INTERACTION_SHORTCUT: InteractionKind =
-            InteractionKind::Shortcut(vec![], String::new());
+            InteractionKind::Shortcut(vec![], String::new(), false);
         const INTERACTION_FILE: InteractionKind = InteractionKind::File(String::new());
+        const INTERACTION_KEY_SCRIPT: InteractionKind = InteractionKind::KeyScript(vec![], 1);
+        const INTERACTION_SCRIPT: InteractionKind = InteractionKind::Script(String::new());
+        const INTERACTION_CLIPBOARD: InteractionKind =
+            InteractionKind::Clipboard(ClipboardAction::Copy(String::new()));
+        const INTERACTION_HTTP: InteractionKind = InteractionKind::Http(HttpRequest {
+            method: String::new(),
+            url: String::new(),
+            headers: vec![],
+            body: String::new(),
+            timeout: 0,
+            response: HttpResponseAction::Log,
+        });
+        const INTERACTION_MQTT: InteractionKind =
+            InteractionKind::Mqtt(String::new(), String::new(), false);
+        const INTERACTION_DBUS: InteractionKind = InteractionKind::DBus(DBusCall {
+            destination: String::new(),
+            path: String::new(),
+            interface: String::new(),
+            method: String::new(),
+            arguments: vec![],
+        });
+        const INTERACTION_MEDIA: InteractionKind =
+            InteractionKind::Media(MediaAction::PlayPause, String::new(), false);
 
         egui::ComboBox::new("properties-interactions-modkey", "")
             .selected_text(format!("{}", interactions.modkey))
@@ -7063,20 +7499,123 @@ fn draw_modkey_interaction_panel(
                 {
                     interactions.modkey = INTERACTION_FILE;
                 }
-            })
-            .response
-            .on_hover_cursor(egui::CursorIcon::PointingHand);
 
-        if interactions.modkey.equals_kind(&INTERACTION_SHORTCUT) {
-            ui.vertical(|ui| {
-                ui.add_space(ui.style().spacing.item_spacing.y * 2.0 + 3.0);
+                if ui
+                    .selectable_label(
+                        interactions.modkey.equals_kind(&INTERACTION_KEY_SCRIPT),
+                        "Key Script",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.modkey = INTERACTION_KEY_SCRIPT;
+                }
 
-                ui.horizontal(|ui| {
-                    ui.add_space(ui.style().spacing.item_spacing.y * 1.5);
+                if ui
+                    .selectable_label(
+                        interactions.modkey.equals_kind(&INTERACTION_HTTP),
+                        "HTTP Request",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.modkey = InteractionKind::Http(HttpRequest::default());
+                }
 
-                    let mode_label_response = ui
-                        .add(egui::Label::new("Text Mode").sense(egui::Sense::click()))
-                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                if ui
+                    .selectable_label(
+                        interactions.modkey.equals_kind(&INTERACTION_MQTT),
+                        "MQTT Publish",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.modkey = INTERACTION_MQTT;
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.modkey.equals_kind(&INTERACTION_DBUS),
+                        "D-Bus Method",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.modkey = INTERACTION_DBUS;
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.modkey.equals_kind(&INTERACTION_MEDIA),
+                        "Media Control",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.modkey = INTERACTION_MEDIA;
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.modkey.equals_kind(&INTERACTION_CLIPBOARD),
+                        "Clipboard",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.modkey = INTERACTION_CLIPBOARD;
+                }
+
+                if ui
+                    .selectable_label(
+                        interactions.modkey.equals_kind(&INTERACTION_SCRIPT),
+                        "Script",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.modkey = INTERACTION_SCRIPT;
+                }
+
+                if ui
+                    .selectable_label(
+                        matches!(interactions.modkey, InteractionKind::Conditional(_, _, _)),
+                        "Conditional",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.modkey = InteractionKind::Conditional(
+                        Condition::Toggle(String::new()),
+                        Box::new(InteractionKind::None()),
+                        Box::new(InteractionKind::None()),
+                    );
+                }
+
+                if ui
+                    .selectable_label(
+                        matches!(interactions.modkey, InteractionKind::Plugin(_)),
+                        "Plugin",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    interactions.modkey = InteractionKind::Plugin(PluginCall::default());
+                }
+            })
+            .response
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+        if interactions.modkey.equals_kind(&INTERACTION_SHORTCUT) {
+            ui.vertical(|ui| {
+                ui.add_space(ui.style().spacing.item_spacing.y * 2.0 + 3.0);
+
+                ui.horizontal(|ui| {
+                    ui.add_space(ui.style().spacing.item_spacing.y * 1.5);
+
+                    let mode_label_response = ui
+                        .add(egui::Label::new("Text Mode").sense(egui::Sense::click()))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
                         .on_hover_text(
                             "When this shortcut is triggered, it simulates \n\
                             typing a text.",
@@ -7111,7 +7650,7 @@ fn draw_modkey_interaction_panel(
         InteractionKind::None() => {
             *should_update = true;
         }
-        InteractionKind::Command(command, _shell) => {
+        InteractionKind::Command(command, _shell, options) => {
             ui.horizontal(|ui| {
                 ui.label("Command");
 
@@ -7164,6 +7703,14 @@ fn draw_modkey_interaction_panel(
                     *should_update = true;
                 }
             }
+
+            draw_command_options_fields(
+                ui,
+                "properties-interactions-modkey-command",
+                component_global_id,
+                options,
+                should_update,
+            );
         }
         InteractionKind::Application(path) => {
             ui.horizontal(|ui| {
@@ -7275,7 +7822,7 @@ fn draw_modkey_interaction_panel(
                 }
             }
         }
-        InteractionKind::Shortcut(keys, text) => {
+        InteractionKind::Shortcut(keys, text, hold) => {
             // Keys
             if properties_shortcut_kind.1 {
                 // `text` must be empty in `keys` mode
@@ -7354,8 +7901,37 @@ fn draw_modkey_interaction_panel(
                     properties_shortcut_key_filter.clear();
                 }
 
+                // Keys can only be held by components that can be released. e.g. buttons
+                if !has_value.0 {
+                    ui.horizontal(|ui| {
+                        let hold_label_response = ui
+                            .add(egui::Label::new("Hold Keys").sense(egui::Sense::click()))
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text(
+                                "Keys are pressed while the button is held down \n\
+                                and released when the button is released.",
+                            );
+
+                        let hold_switch_response = ui
+                            .add(ToggleSwitch::new(*hold, (50.0, 26.0)))
+                            .on_hover_text(
+                                "Keys are pressed while the button is held down \n\
+                                and released when the button is released.",
+                            );
+
+                        if hold_label_response.clicked() || hold_switch_response.clicked() {
+                            *hold = !*hold;
+
+                            *should_update = true;
+                        }
+                    });
+                }
+
             // Text
             } else {
+                // `hold` is only used in `keys` mode
+                *hold = false;
+
                 ui.label("Text");
 
                 const ROWS: usize = 2;
@@ -7437,5 +8013,2106 @@ fn draw_modkey_interaction_panel(
                 }
             }
         }
+        InteractionKind::KeyScript(steps, repeat) => {
+            draw_key_script_fields(
+                ui,
+                "properties-interactions-modkey-key-script",
+                (steps, repeat),
+                properties_shortcut_key_filter,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Http(request) => {
+            draw_http_request_fields(
+                ui,
+                "properties-interactions-modkey-http",
+                request,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Mqtt(topic, payload, retain) => {
+            draw_mqtt_publish_fields(
+                ui,
+                "properties-interactions-modkey-mqtt",
+                (topic, payload, retain),
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::DBus(call) => {
+            draw_dbus_call_fields(
+                ui,
+                "properties-interactions-modkey-dbus",
+                call,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Clipboard(action) => {
+            draw_clipboard_fields(
+                ui,
+                "properties-interactions-modkey-clipboard",
+                action,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Script(script) => {
+            draw_script_fields(
+                ui,
+                "properties-interactions-modkey-script",
+                script,
+                should_update,
+            );
+        }
+        InteractionKind::Media(action, player, report_now_playing) => {
+            draw_media_control_fields(
+                ui,
+                "properties-interactions-modkey-media",
+                (action, player, report_now_playing),
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Conditional(condition, then, otherwise) => {
+            draw_conditional_fields(
+                ui,
+                "properties-interactions-modkey-conditional",
+                InteractionEditor {
+                    component_global_id,
+                    has_value,
+                    properties_shortcut_key_filter,
+                    should_update,
+                },
+                (condition, then, otherwise),
+                default_hint,
+            );
+        }
+        InteractionKind::Plugin(call) => {
+            draw_plugin_fields(
+                ui,
+                "properties-interactions-modkey-plugin",
+                call,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+    }
+}
+
+// Interaction fields
+
+fn draw_plugin_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    call: &mut PluginCall,
+    default_hint: String,
+    // (bool, &str) -> `true/false`, `hint_text`
+    has_value: (bool, &str), // does the component have a value? e.g. potentiometer has 0-99
+    should_update: &mut bool,
+) {
+    // Plugins are requested from the `Service` once, and again when the user asks for it
+    let plugins_id = egui::Id::new("properties-plugins");
+
+    let mut plugins =
+        ui.data(|data| data.get_temp::<Result<Vec<PluginDescription>, String>>(plugins_id));
+
+    if plugins.is_none() {
+        let descriptions = request_plugins();
+
+        ui.data_mut(|data| data.insert_temp(plugins_id, descriptions.clone()));
+
+        plugins = Some(descriptions);
+    }
+
+    let plugins = match plugins {
+        Some(Ok(p)) => p,
+        Some(Err(e)) => {
+            ui.label(egui::RichText::new(e).color(Color::RED));
+
+            return;
+        }
+        None => vec![],
+    };
+
+    let selected_interaction = plugins
+        .iter()
+        .filter(|plugin| plugin.name == call.plugin)
+        .flat_map(|plugin| plugin.interactions.iter())
+        .find(|interaction| interaction.id == call.interaction);
+
+    ui.horizontal(|ui| {
+        ui.label("Interaction");
+
+        egui::ComboBox::new(format!("{}-interaction", id_source), "")
+            .selected_text(match selected_interaction {
+                Some(interaction) => format!("{}: {}", call.plugin, interaction.name),
+                None if call.plugin.is_empty() => "Select".to_string(),
+                None => format!("{}: {}", call.plugin, call.interaction),
+            })
+            .show_ui(ui, |ui| {
+                for plugin in &plugins {
+                    for interaction in &plugin.interactions {
+                        let selected =
+                            call.plugin == plugin.name && call.interaction == interaction.id;
+
+                        if ui
+                            .selectable_label(
+                                selected,
+                                format!("{}: {}", plugin.name, interaction.name),
+                            )
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .clicked()
+                            && !selected
+                        {
+                            *call = PluginCall {
+                                plugin: plugin.name.clone(),
+                                interaction: interaction.id.clone(),
+                                config: interaction
+                                    .fields
+                                    .iter()
+                                    .map(|field| (field.name.clone(), field.default.clone()))
+                                    .collect(),
+                            };
+
+                            *should_update = true;
+                        }
+                    }
+                }
+            })
+            .response
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .button("🗘")
+                .on_hover_text("Fetch the loaded plugins from the `Service`")
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .clicked()
+            {
+                ui.data_mut(|data| {
+                    data.remove::<Result<Vec<PluginDescription>, String>>(plugins_id)
+                });
+            }
+        });
+    });
+
+    let interaction = match selected_interaction {
+        Some(i) => i,
+        None => {
+            ui.label(
+                egui::RichText::new(if plugins.is_empty() {
+                    "No plugins are loaded. Plugins are executables in the `plugins` \
+                    directory next to the config file."
+                } else if call.plugin.is_empty() {
+                    "Select an interaction that's provided by a plugin."
+                } else {
+                    "This plugin or interaction isn't loaded by the `Service`!"
+                })
+                .color(Color::YELLOW.gamma_multiply(0.75)),
+            );
+
+            return;
+        }
+    };
+
+    for field in &interaction.fields {
+        let value = call
+            .config
+            .entry(field.name.clone())
+            .or_insert(field.default.clone());
+
+        ui.horizontal(|ui| {
+            ui.label(if field.label.is_empty() {
+                &field.name
+            } else {
+                &field.label
+            });
+
+            if field.kind == PluginFieldKind::String || !field.description.is_empty() {
+                let hint = if field.kind == PluginFieldKind::String {
+                    default_hint.clone() + field.description.as_str()
+                } else {
+                    field.description.clone()
+                };
+                let hint = hint
+                    + if has_value.0 && field.kind == PluginFieldKind::String {
+                        format!("\n\n({})", has_value.1)
+                    } else {
+                        String::new()
+                    }
+                    .as_str();
+
+                if !hint.trim().is_empty() {
+                    ui.add(
+                        egui::Label::new(
+                            egui::RichText::new("ℹ").color(Color::LIGHT_BLUE.gamma_multiply(0.75)),
+                        )
+                        .sense(egui::Sense::hover()),
+                    )
+                    .on_hover_cursor(egui::CursorIcon::Help)
+                    .on_hover_text(
+                        egui::RichText::new(hint.trim())
+                            .color(Color::LIGHT_BLUE)
+                            .size(16.0),
+                    );
+                }
+            }
+
+            ui.with_layout(
+                egui::Layout::right_to_left(egui::Align::Center),
+                |ui| match field.kind {
+                    PluginFieldKind::Number => {
+                        let mut number = value.as_f64().unwrap_or(0.0);
+
+                        if ui.add(DragValue::new(&mut number).speed(1)).changed() {
+                            *value = serde_json::json!(number);
+
+                            *should_update = true;
+                        }
+                    }
+                    PluginFieldKind::Boolean => {
+                        let enabled = value.as_bool().unwrap_or(false);
+
+                        if ui.add(ToggleSwitch::new(enabled, (50.0, 26.0))).clicked() {
+                            *value = serde_json::Value::Bool(!enabled);
+
+                            *should_update = true;
+                        }
+                    }
+                    PluginFieldKind::Choice => {
+                        let current = value.as_str().unwrap_or_default().to_string();
+
+                        egui::ComboBox::new(format!("{}-{}", id_source, field.name), "")
+                            .selected_text(&current)
+                            .show_ui(ui, |ui| {
+                                for choice in &field.choices {
+                                    if ui
+                                        .selectable_label(*choice == current, choice)
+                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                        .clicked()
+                                    {
+                                        *value = serde_json::Value::String(choice.clone());
+
+                                        *should_update = true;
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_cursor(egui::CursorIcon::PointingHand);
+                    }
+                    PluginFieldKind::String => (),
+                },
+            );
+        });
+
+        if field.kind == PluginFieldKind::String {
+            let mut text = value.as_str().unwrap_or_default().to_string();
+
+            if ui
+                .add(
+                    egui::TextEdit::singleline(&mut text)
+                        .margin(Vec2::new(8.0, 8.0))
+                        .desired_width(f32::INFINITY),
+                )
+                .changed()
+            {
+                *value = serde_json::Value::String(text);
+
+                *should_update = true;
+            }
+        }
+    }
+}
+
+fn draw_trigger_options_fields(
+    ui: &mut Ui,
+    options: &mut TriggerOptions,
+    can_confirm: bool, // Only buttons can be confirmed
+    should_update: &mut bool,
+) {
+    let format_duration = |value: f64, _| {
+        if value == 0.0 {
+            "None".to_string()
+        } else {
+            format!("{}ms", value)
+        }
+    };
+
+    ui.horizontal(|ui| {
+        ui.label("Cooldown");
+
+        if ui
+            .add(
+                DragValue::new(&mut options.cooldown)
+                    .speed(10)
+                    .range(0..=60_000)
+                    .custom_formatter(format_duration),
+            )
+            .on_hover_text("Minimum interval between two triggers of this component")
+            .changed()
+        {
+            *should_update = true;
+        }
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add(
+                    DragValue::new(&mut options.debounce)
+                        .speed(1)
+                        .range(0..=1000)
+                        .custom_formatter(format_duration),
+                )
+                .on_hover_text(
+                    "Events closer than this to the previous one are ignored.\n\
+                    Useful for mechanical bounce or messages that are reported twice.",
+                )
+                .changed()
+            {
+                *should_update = true;
+            }
+
+            ui.label("Debounce");
+        });
+    });
+
+    ui.horizontal(|ui| {
+        let running_label_response = ui
+            .add(egui::Label::new("Ignore While Running").sense(egui::Sense::click()))
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text(
+                "Ignore this component while its previous run is still active.\n\
+                e.g. a long command, script or key script",
+            );
+
+        let running_switch_response = ui
+            .add(ToggleSwitch::new(
+                options.ignore_while_running,
+                (50.0, 26.0),
+            ))
+            .on_hover_text(
+                "Ignore this component while its previous run is still active.\n\
+                e.g. a long command, script or key script",
+            );
+
+        if running_label_response.clicked() || running_switch_response.clicked() {
+            options.ignore_while_running = !options.ignore_while_running;
+
+            *should_update = true;
+        }
+    });
+
+    if !can_confirm {
+        return;
     }
+
+    ui.horizontal(|ui| {
+        ui.label("Confirmation");
+
+        let hint = "Risky interactions can require a confirmation first:\n\
+            \tDouble Press: press again within the duration\n\
+            \tLong Press: hold the button for the duration\n\
+            \tPrompt: confirm a dialog on the desktop\n\n\
+            The device's display (and the LED) shows that it's waiting.";
+
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new("ℹ").color(Color::LIGHT_BLUE.gamma_multiply(0.75)),
+            )
+            .sense(egui::Sense::hover()),
+        )
+        .on_hover_cursor(egui::CursorIcon::Help)
+        .on_hover_text(
+            egui::RichText::new(hint)
+                .color(Color::LIGHT_BLUE)
+                .size(16.0),
+        );
+
+        egui::ComboBox::new("properties-trigger-confirmation", "")
+            .selected_text(format!("{}", options.confirmation))
+            .show_ui(ui, |ui| {
+                let confirmations = [
+                    Confirmation::None,
+                    Confirmation::DoublePress(2000),
+                    Confirmation::LongPress(1000),
+                    Confirmation::Prompt,
+                ];
+
+                for new_confirmation in confirmations {
+                    let selected = std::mem::discriminant(&options.confirmation)
+                        == std::mem::discriminant(&new_confirmation);
+
+                    if ui
+                        .selectable_label(selected, format!("{}", new_confirmation))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                        && !selected
+                    {
+                        options.confirmation = new_confirmation;
+
+                        *should_update = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if let Confirmation::DoublePress(duration) | Confirmation::LongPress(duration) =
+                &mut options.confirmation
+            {
+                if ui
+                    .add(
+                        DragValue::new(duration)
+                            .speed(10)
+                            .range(100..=10_000)
+                            .suffix("ms"),
+                    )
+                    .changed()
+                {
+                    *should_update = true;
+                }
+            }
+        });
+    });
+
+    if options.confirmation == Confirmation::None {
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Armed LED");
+
+        if ui
+            .add(
+                DragValue::new(&mut options.armed_led)
+                    .speed(1)
+                    .range(0..=255)
+                    .custom_formatter(|value, _| {
+                        if value == 0.0 {
+                            "None".to_string()
+                        } else {
+                            format!("{}", value)
+                        }
+                    }),
+            )
+            .on_hover_text("This LED is lit while waiting for confirmation")
+            .changed()
+        {
+            *should_update = true;
+        }
+    });
+}
+
+fn draw_conditional_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    InteractionEditor {
+        component_global_id,
+        has_value,
+        properties_shortcut_key_filter,
+        should_update,
+    }: InteractionEditor,
+    (condition, then, otherwise): (
+        &mut Condition,
+        &mut Box<InteractionKind>,
+        &mut Box<InteractionKind>,
+    ),
+    default_hint: String,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Condition");
+
+        egui::ComboBox::new(format!("{}-condition", id_source), "")
+            .selected_text(format!("{}", condition))
+            .show_ui(ui, |ui| {
+                let conditions = [
+                    Condition::ProcessRunning(String::new()),
+                    Condition::FileExists(String::new()),
+                    Condition::CommandSucceeds(String::new(), String::new()),
+                    Condition::TimeBetween("09:00".to_string(), "17:00".to_string()),
+                    Condition::ComponentValue(String::new(), Comparison::Greater, String::new()),
+                    Condition::Toggle(String::new()),
+                ];
+
+                for new_condition in conditions {
+                    let selected =
+                        std::mem::discriminant(condition) == std::mem::discriminant(&new_condition);
+
+                    if ui
+                        .selectable_label(selected, format!("{}", new_condition))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                        && !selected
+                    {
+                        *condition = new_condition;
+
+                        *should_update = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+        let hint = match condition {
+            Condition::ProcessRunning(_) => {
+                "Met when a process with this exact name is running.\n\n\
+                Example:\n\tfirefox\nor\n\tfirefox.exe"
+            }
+            Condition::FileExists(_) => {
+                "Met when the file or folder exists.\n\n\
+                Example:\n\t/tmp/presentation-mode"
+            }
+            Condition::CommandSucceeds(_, _) => {
+                "Met when the command exits successfully (status 0).\n\n\
+                Example:\n\tping -c 1 192.168.1.10"
+            }
+            Condition::TimeBetween(_, _) => {
+                "Met between these times of day (HH:MM).\n\
+                Ranges that end before they start continue past midnight."
+            }
+            Condition::ComponentValue(_, _, _) => {
+                "Compares the last value of another component.\n\n\
+                Example:\n\tPotentiometer:1 > 50"
+            }
+            Condition::Toggle(_) => {
+                "Flips on each use, so the interactions below alternate.\n\
+                The first use runs \"Then\".\n\n\
+                Scripts can read it with get_var(\"name\")."
+            }
+        };
+
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new("ℹ").color(Color::LIGHT_BLUE.gamma_multiply(0.75)),
+            )
+            .sense(egui::Sense::hover()),
+        )
+        .on_hover_cursor(egui::CursorIcon::Help)
+        .on_hover_text(
+            egui::RichText::new(hint)
+                .color(Color::LIGHT_BLUE)
+                .size(16.0),
+        );
+    });
+
+    // Returns `true` if the text was changed
+    let text_field = |ui: &mut Ui, text: &mut String, width: f32, hint_text: &str| {
+        ui.add(
+            egui::TextEdit::singleline(text)
+                .desired_width(width)
+                .hint_text(hint_text),
+        )
+        .changed()
+    };
+
+    match condition {
+        Condition::ProcessRunning(name) => {
+            ui.label("Process Name");
+
+            *should_update |= text_field(ui, name, f32::INFINITY, "firefox");
+        }
+        Condition::FileExists(path) => {
+            ui.label("File Full Path");
+
+            *should_update |= text_field(ui, path, f32::INFINITY, "/tmp/presentation-mode");
+        }
+        Condition::CommandSucceeds(command, _shell) => {
+            ui.label("Command");
+
+            *should_update |= text_field(ui, command, f32::INFINITY, "pgrep -x obs");
+        }
+        Condition::TimeBetween(start, end) => {
+            ui.horizontal(|ui| {
+                ui.label("From");
+
+                *should_update |= text_field(ui, start, 60.0, "09:00");
+
+                ui.label("To");
+
+                *should_update |= text_field(ui, end, 60.0, "17:00");
+            });
+        }
+        Condition::ComponentValue(other_component_global_id, comparison, value) => {
+            ui.horizontal(|ui| {
+                *should_update |=
+                    text_field(ui, other_component_global_id, 140.0, "Potentiometer:1");
+
+                egui::ComboBox::new(format!("{}-comparison", id_source), "")
+                    .selected_text(format!("{}", comparison))
+                    .width(50.0)
+                    .show_ui(ui, |ui| {
+                        let comparisons = [
+                            Comparison::Equal,
+                            Comparison::NotEqual,
+                            Comparison::Greater,
+                            Comparison::Less,
+                        ];
+
+                        for new_comparison in comparisons {
+                            let selected = *comparison == new_comparison;
+
+                            if ui
+                                .selectable_label(selected, format!("{}", new_comparison))
+                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                .clicked()
+                                && !selected
+                            {
+                                *comparison = new_comparison;
+
+                                *should_update = true;
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+                *should_update |= text_field(ui, value, f32::INFINITY, "50");
+            });
+        }
+        Condition::Toggle(name) => {
+            ui.label("Toggle Name");
+
+            *should_update |= text_field(ui, name, f32::INFINITY, "mic_muted");
+        }
+    }
+
+    for (label, kind) in [("Then", then), ("Otherwise", otherwise)] {
+        ui.add_space(ui.style().spacing.item_spacing.y);
+
+        ui.group(|ui| {
+            draw_nested_interaction_fields(
+                ui,
+                &format!("{}-{}", id_source, label.to_lowercase()),
+                label,
+                InteractionEditor {
+                    component_global_id,
+                    has_value,
+                    properties_shortcut_key_filter,
+                    should_update,
+                },
+                kind,
+                default_hint.clone(),
+            );
+        });
+    }
+}
+
+/// Interactions inside a `Conditional`, `Shortcut` and `Conditional` can't be nested here
+/// (`Key Script` covers shortcuts), but they still work if they're set in the config file
+fn draw_nested_interaction_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    label: &str,
+    InteractionEditor {
+        component_global_id,
+        has_value,
+        properties_shortcut_key_filter,
+        should_update,
+    }: InteractionEditor,
+    kind: &mut InteractionKind,
+    default_hint: String,
+) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+
+        egui::ComboBox::new(format!("{}-kind", id_source), "")
+            .selected_text(format!("{}", kind))
+            .show_ui(ui, |ui| {
+                let kinds = [
+                    ("None", InteractionKind::None()),
+                    (
+                        "Command",
+                        InteractionKind::Command(
+                            String::new(),
+                            String::new(),
+                            CommandOptions::default(),
+                        ),
+                    ),
+                    ("Application", InteractionKind::Application(String::new())),
+                    ("Website", InteractionKind::Website(String::new())),
+                    ("File", InteractionKind::File(String::new())),
+                    ("Key Script", InteractionKind::KeyScript(vec![], 1)),
+                    (
+                        "HTTP Request",
+                        InteractionKind::Http(HttpRequest::default()),
+                    ),
+                    (
+                        "MQTT Publish",
+                        InteractionKind::Mqtt(String::new(), String::new(), false),
+                    ),
+                    (
+                        "D-Bus Method",
+                        InteractionKind::DBus(DBusCall {
+                            destination: String::new(),
+                            path: String::new(),
+                            interface: String::new(),
+                            method: String::new(),
+                            arguments: vec![],
+                        }),
+                    ),
+                    (
+                        "Media Control",
+                        InteractionKind::Media(MediaAction::PlayPause, String::new(), false),
+                    ),
+                    (
+                        "Clipboard",
+                        InteractionKind::Clipboard(ClipboardAction::Copy(String::new())),
+                    ),
+                    ("Script", InteractionKind::Script(String::new())),
+                    ("Plugin", InteractionKind::Plugin(PluginCall::default())),
+                ];
+
+                for (name, new_kind) in kinds {
+                    let selected = kind.equals_kind(&new_kind);
+
+                    if ui
+                        .selectable_label(selected, name)
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                        && !selected
+                    {
+                        *kind = new_kind;
+
+                        *should_update = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+    });
+
+    let mut path_field = |ui: &mut Ui, field_label: &str, text: &mut String| {
+        ui.label(field_label);
+
+        if ui
+            .add(egui::TextEdit::singleline(text).desired_width(f32::INFINITY))
+            .changed()
+        {
+            *should_update = true;
+        }
+    };
+
+    match kind {
+        InteractionKind::None() => (),
+        InteractionKind::Command(command, _shell, options) => {
+            path_field(ui, "Command", command);
+
+            draw_command_options_fields(ui, id_source, component_global_id, options, should_update);
+        }
+        InteractionKind::Application(path) => path_field(ui, "Application Full Path", path),
+        InteractionKind::Website(url) => path_field(ui, "Website URL", url),
+        InteractionKind::File(path) => path_field(ui, "File Full Path", path),
+        InteractionKind::KeyScript(steps, repeat) => {
+            draw_key_script_fields(
+                ui,
+                id_source,
+                (steps, repeat),
+                properties_shortcut_key_filter,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Http(request) => {
+            draw_http_request_fields(
+                ui,
+                id_source,
+                request,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Mqtt(topic, payload, retain) => {
+            draw_mqtt_publish_fields(
+                ui,
+                id_source,
+                (topic, payload, retain),
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::DBus(call) => {
+            draw_dbus_call_fields(ui, id_source, call, default_hint, has_value, should_update);
+        }
+        InteractionKind::Media(action, player, report_now_playing) => {
+            draw_media_control_fields(
+                ui,
+                id_source,
+                (action, player, report_now_playing),
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Clipboard(action) => {
+            draw_clipboard_fields(
+                ui,
+                id_source,
+                action,
+                default_hint,
+                has_value,
+                should_update,
+            );
+        }
+        InteractionKind::Script(script) => {
+            draw_script_fields(ui, id_source, script, should_update);
+        }
+        InteractionKind::Plugin(call) => {
+            draw_plugin_fields(ui, id_source, call, default_hint, has_value, should_update);
+        }
+        InteractionKind::Shortcut(_, _, _) | InteractionKind::Conditional(_, _, _) => {
+            ui.label(
+                egui::RichText::new("This interaction can only be edited in the config file.")
+                    .color(egui::Color32::from_gray(127)),
+            );
+        }
+    }
+}
+
+fn draw_clipboard_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    action: &mut ClipboardAction,
+    default_hint: String,
+    // (bool, &str) -> `true/false`, `hint_text`
+    has_value: (bool, &str), // does the component have a value? e.g. potentiometer has 0-99
+    should_update: &mut bool,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Action");
+
+        egui::ComboBox::new(format!("{}-action", id_source), "")
+            .selected_text(format!("{}", action))
+            .show_ui(ui, |ui| {
+                let actions = [
+                    ClipboardAction::Copy(String::new()),
+                    ClipboardAction::Paste(String::new(), true),
+                    ClipboardAction::History(false),
+                ];
+
+                for new_action in actions {
+                    let selected =
+                        std::mem::discriminant(action) == std::mem::discriminant(&new_action);
+
+                    if ui
+                        .selectable_label(selected, format!("{}", new_action))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                        && !selected
+                    {
+                        // Keep the snippet when switching between copy and paste
+                        *action = match (&action, new_action) {
+                            (
+                                ClipboardAction::Copy(snippet) | ClipboardAction::Paste(snippet, _),
+                                ClipboardAction::Copy(_),
+                            ) => ClipboardAction::Copy(snippet.clone()),
+                            (
+                                ClipboardAction::Copy(snippet) | ClipboardAction::Paste(snippet, _),
+                                ClipboardAction::Paste(_, restore),
+                            ) => ClipboardAction::Paste(snippet.clone(), restore),
+                            (_, new_action) => new_action,
+                        };
+
+                        *should_update = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+    });
+
+    let (snippet, toggle) = match action {
+        ClipboardAction::Copy(snippet) => (Some(snippet), None),
+        ClipboardAction::Paste(snippet, restore) => (
+            Some(snippet),
+            Some((
+                restore,
+                "Restore Clipboard",
+                "The previous content of the clipboard is put back after pasting.",
+            )),
+        ),
+        ClipboardAction::History(paste) => (
+            None,
+            Some((
+                paste,
+                "Paste",
+                "Also paste the selected entry.\n\
+                Each use selects an older entry of the clipboard history.",
+            )),
+        ),
+    };
+
+    if let Some((value, label, hint)) = toggle {
+        ui.horizontal(|ui| {
+            let label_response = ui
+                .add(egui::Label::new(label).sense(egui::Sense::click()))
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text(hint);
+
+            let switch_response = ui
+                .add(ToggleSwitch::new(*value, (50.0, 26.0)))
+                .on_hover_text(hint);
+
+            if label_response.clicked() || switch_response.clicked() {
+                *value = !*value;
+
+                *should_update = true;
+            }
+        });
+    }
+
+    let snippet = match snippet {
+        Some(s) => s,
+        None => return,
+    };
+
+    ui.horizontal(|ui| {
+        ui.label("Snippet");
+
+        let hint = default_hint
+            + if has_value.0 {
+                "Example:\n\tVolume: {value}%"
+            } else {
+                "Example:\n\tBest regards,\n\t{env:USER}"
+            };
+        let hint = hint
+            + if has_value.0 {
+                format!("\n\n({})", has_value.1)
+            } else {
+                String::new()
+            }
+            .as_str();
+
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new("ℹ").color(Color::LIGHT_BLUE.gamma_multiply(0.75)),
+            )
+            .sense(egui::Sense::hover()),
+        )
+        .on_hover_cursor(egui::CursorIcon::Help)
+        .on_hover_text(
+            egui::RichText::new(hint)
+                .color(Color::LIGHT_BLUE)
+                .size(16.0),
+        );
+    });
+
+    const ROWS: usize = 4;
+
+    let mut response = None;
+
+    egui::ScrollArea::vertical()
+        .id_salt(format!("{}-snippet", id_source))
+        .max_height((ROWS + 1) as f32 * 20.0)
+        .show(ui, |ui| {
+            response = Some(
+                ui.add(
+                    egui::TextEdit::multiline(snippet)
+                        .desired_rows(ROWS)
+                        .desired_width(f32::INFINITY),
+                ),
+            );
+        });
+
+    if let Some(r) = response {
+        if r.changed() {
+            *should_update = true;
+        }
+    }
+}
+
+fn draw_script_fields(ui: &mut Ui, id_source: &str, script: &mut String, should_update: &mut bool) {
+    ui.horizontal(|ui| {
+        ui.label("Script");
+
+        let hint = "Scripts are written in Rhai (rhai.rs) and are stopped after 5 seconds.\n\n\
+            Constants:\n\
+            \tvalue, prev_value, component, profile, modkey\n\n\
+            Functions:\n\
+            \trun_interaction(\"Button:2\") / run_interaction(\"Button:2\", modkey)\n\
+            \tsend_keys([\"Control\", \"c\"]) / type_text(\"Hello\")\n\
+            \tset_led(1, 255) / display(\"Hello\")\n\
+            \tswitch_profile(\"Work\") / switch_profile(1)\n\
+            \tget_var(\"name\") / set_var(\"name\", value) (persistent)\n\
+            \tget_value(\"Potentiometer:1\")\n\
+            \tsleep(100) / print(\"log\")\n\n\
+            Example:\n\
+            \tif get_value(\"Potentiometer:1\") > 50 && modkey {\n\
+            \t\tswitch_profile(\"Gaming\");\n\
+            \t}";
+
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new("ℹ").color(Color::LIGHT_BLUE.gamma_multiply(0.75)),
+            )
+            .sense(egui::Sense::hover()),
+        )
+        .on_hover_cursor(egui::CursorIcon::Help)
+        .on_hover_text(
+            egui::RichText::new(hint)
+                .color(Color::LIGHT_BLUE)
+                .size(16.0),
+        );
+    });
+
+    const ROWS: usize = 10;
+
+    let mut response = None;
+
+    egui::ScrollArea::vertical()
+        .id_salt(format!("{}-code", id_source))
+        .max_height((ROWS + 1) as f32 * 20.0)
+        .show(ui, |ui| {
+            response = Some(
+                ui.add(
+                    egui::TextEdit::multiline(script)
+                        .code_editor()
+                        .desired_rows(ROWS)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("let count = get_var(\"count\") ?? 0;\nset_var(\"count\", count + 1);\ndisplay(`Pressed ${count + 1} times`);"),
+                ),
+            );
+        });
+
+    if let Some(r) = response {
+        if r.changed() {
+            *should_update = true;
+        }
+    }
+}
+
+fn draw_command_options_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    component_global_id: &str,
+    options: &mut CommandOptions,
+    should_update: &mut bool,
+) {
+    ui.horizontal(|ui| {
+        let shell_label_response = ui
+            .add(egui::Label::new("Use Shell").sense(egui::Sense::click()))
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text(
+                "Run the command through the shell (`sh -c` or `cmd /C`).\n\
+                When disabled, the program is run directly with its arguments.",
+            );
+
+        let shell_switch_response = ui
+            .add(ToggleSwitch::new(options.use_shell, (50.0, 26.0)))
+            .on_hover_text(
+                "Run the command through the shell (`sh -c` or `cmd /C`).\n\
+                When disabled, the program is run directly with its arguments.",
+            );
+
+        if shell_label_response.clicked() || shell_switch_response.clicked() {
+            options.use_shell = !options.use_shell;
+
+            *should_update = true;
+        }
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add(
+                    DragValue::new(&mut options.timeout)
+                        .speed(100)
+                        .range(0..=3_600_000)
+                        .custom_formatter(|value, _| {
+                            if value == 0.0 {
+                                "None".to_string()
+                            } else {
+                                format!("{}ms", value)
+                            }
+                        }),
+                )
+                .on_hover_text("The command is killed after this duration")
+                .changed()
+            {
+                *should_update = true;
+            }
+
+            ui.label("Timeout");
+        });
+    });
+
+    ui.horizontal(|ui| {
+        let kill_label_response = ui
+            .add(egui::Label::new("Kill Previous").sense(egui::Sense::click()))
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text("Kill the last run of this command if it's still running.");
+
+        let kill_switch_response = ui
+            .add(ToggleSwitch::new(options.kill_previous, (50.0, 26.0)))
+            .on_hover_text("Kill the last run of this command if it's still running.");
+
+        if kill_label_response.clicked() || kill_switch_response.clicked() {
+            options.kill_previous = !options.kill_previous;
+
+            *should_update = true;
+        }
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            let display_switch_response = ui
+                .add(ToggleSwitch::new(options.display_output, (50.0, 26.0)))
+                .on_hover_text("Show the last line of the output on the device's display.");
+
+            let display_label_response = ui
+                .add(egui::Label::new("Show on Display").sense(egui::Sense::click()))
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text("Show the last line of the output on the device's display.");
+
+            if display_label_response.clicked() || display_switch_response.clicked() {
+                options.display_output = !options.display_output;
+
+                *should_update = true;
+            }
+        });
+    });
+
+    ui.label("Working Directory");
+
+    if ui
+        .add(
+            egui::TextEdit::singleline(&mut options.working_directory)
+                .margin(Vec2::new(8.0, 8.0))
+                .desired_width(f32::INFINITY)
+                .hint_text("~/projects/website"),
+        )
+        .changed()
+    {
+        *should_update = true;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Environment Variables");
+
+        if ui
+            .small_button("+")
+            .on_hover_text("Add a variable")
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .clicked()
+        {
+            options.environment.push((String::new(), String::new()));
+
+            *should_update = true;
+        }
+    });
+
+    let mut removed_variable = None;
+
+    for (index, (name, value)) in options.environment.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            let spacing = ui.spacing().item_spacing.x;
+            let input_width = (ui.available_width() - spacing * 2.0 - 24.0) / 2.0;
+
+            if ui
+                .add_sized(
+                    (input_width, 0.0),
+                    egui::TextEdit::singleline(name).hint_text("Name"),
+                )
+                .changed()
+            {
+                *should_update = true;
+            }
+
+            if ui
+                .add_sized(
+                    (input_width, 0.0),
+                    egui::TextEdit::singleline(value).hint_text("Value"),
+                )
+                .changed()
+            {
+                *should_update = true;
+            }
+
+            if ui
+                .small_button("🗑")
+                .on_hover_text("Remove this variable")
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .clicked()
+            {
+                removed_variable = Some(index);
+            }
+        });
+    }
+
+    if let Some(index) = removed_variable {
+        options.environment.remove(index);
+
+        *should_update = true;
+    }
+
+    // The last output is requested from the `Service` when the user asks for it, later runs
+    // replace it through `ServerEvent::CommandOutput`
+    let output_id = command_output_id(component_global_id);
+
+    ui.horizontal(|ui| {
+        ui.label("Last Output");
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .button("🗘")
+                .on_hover_text("Fetch the output of the last run from the `Service`")
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .clicked()
+            {
+                let output = request_command_output(component_global_id);
+
+                ui.data_mut(|data| data.insert_temp(output_id, output));
+            }
+        });
+    });
+
+    let output = ui.data(|data| data.get_temp::<Result<CommandOutput, String>>(output_id));
+
+    ui.vertical_centered_justified(|ui| {
+        ui.group(|ui| match output {
+            None => {
+                ui.label("Click 🗘 to fetch the output.");
+            }
+            Some(Err(e)) => {
+                ui.label(egui::RichText::new(e).color(Color::RED));
+            }
+            Some(Ok(output)) if output.command.is_empty() => {
+                ui.label("This command hasn't been run yet!");
+            }
+            Some(Ok(output)) => {
+                ui.label(
+                    egui::RichText::new(format!("{} ({})", output.command, output.status))
+                        .color(Color::LIGHT_BLUE),
+                );
+
+                egui::ScrollArea::vertical()
+                    .id_salt(format!("{}-output", id_source))
+                    .max_height(120.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::Label::new(egui::RichText::new(output.output).monospace()).wrap(),
+                        );
+                    });
+            }
+        });
+    });
+}
+
+fn command_output_id(component_global_id: &str) -> egui::Id {
+    egui::Id::new(format!("{}-command-output", component_global_id))
+}
+
+fn draw_key_script_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    (steps, repeat): (&mut Vec<KeyStep>, &mut u32),
+    properties_shortcut_key_filter: &mut String,
+    // (bool, &str) -> `true/false`, `hint_text`
+    has_value: (bool, &str), // does the component have a value? e.g. potentiometer has 0-99
+    should_update: &mut bool,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Steps");
+
+        let hint = format!(
+            "Steps are run one after another.\n\
+            Example:\n\tCtrl+K, Ctrl+C\n\tCtrl+L, 100ms, \"hello\", Return{}",
+            if has_value.0 {
+                format!(
+                    "\n\nYou can pass this component's value\n\
+                    to text steps by adding {{value}}\n\n({})",
+                    has_value.1
+                )
+            } else {
+                String::new()
+            }
+        );
+
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new("ℹ").color(Color::LIGHT_BLUE.gamma_multiply(0.75)),
+            )
+            .sense(egui::Sense::hover()),
+        )
+        .on_hover_cursor(egui::CursorIcon::Help)
+        .on_hover_text(
+            egui::RichText::new(hint)
+                .color(Color::LIGHT_BLUE)
+                .size(16.0),
+        );
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add(DragValue::new(repeat).range(1..=100).prefix("× "))
+                .on_hover_text("How many times the script is repeated")
+                .changed()
+            {
+                *should_update = true;
+            }
+
+            ui.label("Repeat");
+        });
+    });
+
+    if steps.is_empty() {
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                ui.label("No steps were added yet!");
+            });
+        });
+    }
+
+    let mut moved_step = None;
+    let mut removed_step = None;
+    let steps_count = steps.len();
+
+    for (index, step) in steps.iter_mut().enumerate() {
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(format!("{}.", index + 1))
+                        .color(egui::Color32::from_gray(127)),
+                );
+
+                match step {
+                    KeyStep::Chord(keys) => {
+                        ui.label("Chord");
+
+                        egui::ComboBox::new(format!("{}-step-{}-keys", id_source, index), "")
+                            .selected_text("Add Keys")
+                            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+                            .show_ui(ui, |ui| {
+                                let filter_response = ui.add_sized(
+                                    (160.0, 0.0),
+                                    egui::TextEdit::singleline(properties_shortcut_key_filter)
+                                        .margin(Vec2::new(8.0, 8.0))
+                                        .hint_text("Search"),
+                                );
+
+                                filter_response.request_focus();
+
+                                let filtered_options: Vec<_> = KEYS
+                                    .iter()
+                                    .filter(|option| {
+                                        format!("{}", option).to_lowercase().contains(
+                                            &properties_shortcut_key_filter.to_lowercase(),
+                                        )
+                                    })
+                                    .collect();
+
+                                for key in filtered_options {
+                                    if ui
+                                        .selectable_label(false, format!("{}", key))
+                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                        .clicked()
+                                    {
+                                        ui.memory_mut(|mem| mem.toggle_popup(ui.id()));
+
+                                        keys.push(key.clone());
+
+                                        properties_shortcut_key_filter.clear();
+
+                                        *should_update = true;
+                                    }
+                                }
+
+                                // Dummy items to fill the space even if there's no item
+                                ui.add_space((32.0 * 5.0) - 4.0);
+                            })
+                            .response
+                            .on_hover_cursor(egui::CursorIcon::PointingHand);
+                    }
+                    KeyStep::Text(text) => {
+                        ui.label("Text");
+
+                        if ui
+                            .add(
+                                egui::TextEdit::singleline(text)
+                                    .desired_width(ui.available_width() - 90.0),
+                            )
+                            .changed()
+                        {
+                            *should_update = true;
+                        }
+                    }
+                    KeyStep::Delay(duration) => {
+                        ui.label("Delay");
+
+                        if ui
+                            .add(DragValue::new(duration).range(1..=60000).suffix("ms"))
+                            .changed()
+                        {
+                            *should_update = true;
+                        }
+                    }
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui
+                        .small_button("🗑")
+                        .on_hover_text("Remove this step")
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                    {
+                        removed_step = Some(index);
+                    }
+
+                    if index + 1 < steps_count
+                        && ui
+                            .small_button("⏷")
+                            .on_hover_text("Move down")
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .clicked()
+                    {
+                        moved_step = Some((index, index + 1));
+                    }
+
+                    if index > 0
+                        && ui
+                            .small_button("⏶")
+                            .on_hover_text("Move up")
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .clicked()
+                    {
+                        moved_step = Some((index, index - 1));
+                    }
+                });
+            });
+
+            if let KeyStep::Chord(keys) = step {
+                if keys.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No keys were added yet!")
+                            .color(egui::Color32::from_gray(127)),
+                    );
+                } else {
+                    ui.add(
+                        ItemList::new(
+                            keys,
+                            26.0,
+                            egui::Color32::from_gray(50),
+                            Color::WHITE,
+                            Color::ACCENT.gamma_multiply(0.15),
+                            egui::Color32::from_gray(12),
+                        )
+                        .spacing(2.0)
+                        .on_item_removed(|_item| {
+                            *should_update = true;
+                        }),
+                    );
+                }
+            }
+        });
+    }
+
+    if let Some((from, to)) = moved_step {
+        steps.swap(from, to);
+
+        *should_update = true;
+    }
+
+    if let Some(index) = removed_step {
+        steps.remove(index);
+
+        *should_update = true;
+    }
+
+    ui.horizontal_top(|ui| {
+        let spacing = ui.spacing().item_spacing.x;
+
+        let total_width = ui.available_width();
+        let button_width = (total_width - spacing * 2.0) / 3.0;
+
+        let new_steps = [
+            ("+ Chord", KeyStep::Chord(vec![])),
+            ("+ Text", KeyStep::Text(String::new())),
+            ("+ Delay", KeyStep::Delay(100)),
+        ];
+
+        for (label, new_step) in new_steps {
+            if ui
+                .add_sized([button_width, 0.0], egui::Button::new(label))
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .clicked()
+            {
+                steps.push(new_step);
+
+                *should_update = true;
+            }
+        }
+    });
+}
+
+fn draw_http_request_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    request: &mut HttpRequest,
+    default_hint: String,
+    // (bool, &str) -> `true/false`, `hint_text`
+    has_value: (bool, &str), // does the component have a value? e.g. potentiometer has 0-99
+    should_update: &mut bool,
+) {
+    const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+    ui.horizontal(|ui| {
+        ui.label("Method");
+
+        egui::ComboBox::new(format!("{}-method", id_source), "")
+            .selected_text(request.method.clone())
+            .show_ui(ui, |ui| {
+                for method in METHODS {
+                    if ui
+                        .selectable_label(request.method == method, method)
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                    {
+                        request.method = method.to_string();
+
+                        *should_update = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+        ui.label("Timeout");
+
+        if ui
+            .add(
+                DragValue::new(&mut request.timeout)
+                    .speed(100)
+                    .range(100..=60000)
+                    .suffix("ms"),
+            )
+            .changed()
+        {
+            *should_update = true;
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("URL");
+
+        let hint = default_hint
+            + if has_value.0 {
+                "Example:\n\thttp://127.0.0.1:8080/volume?level={value}"
+            } else {
+                "Example:\n\thttps://ci.example.com/job/deploy/build"
+            };
+        let hint = hint
+            + if has_value.0 {
+                format!("\n\n({})", has_value.1)
+            } else {
+                String::new()
+            }
+            .as_str();
+
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new("ℹ").color(Color::LIGHT_BLUE.gamma_multiply(0.75)),
+            )
+            .sense(egui::Sense::hover()),
+        )
+        .on_hover_cursor(egui::CursorIcon::Help)
+        .on_hover_text(
+            egui::RichText::new(hint)
+                .color(Color::LIGHT_BLUE)
+                .size(16.0),
+        );
+    });
+
+    if ui
+        .add(
+            egui::TextEdit::singleline(&mut request.url)
+                .margin(Vec2::new(8.0, 8.0))
+                .desired_width(f32::INFINITY),
+        )
+        .changed()
+    {
+        *should_update = true;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Headers");
+
+        if ui
+            .small_button("+")
+            .on_hover_text("Add a header")
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .clicked()
+        {
+            request.headers.push((String::new(), String::new()));
+
+            *should_update = true;
+        }
+    });
+
+    let mut removed_header = None;
+
+    for (index, (name, value)) in request.headers.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            let spacing = ui.spacing().item_spacing.x;
+            let input_width = (ui.available_width() - spacing * 2.0 - 24.0) / 2.0;
+
+            if ui
+                .add_sized(
+                    (input_width, 0.0),
+                    egui::TextEdit::singleline(name).hint_text("Name"),
+                )
+                .changed()
+            {
+                *should_update = true;
+            }
+
+            if ui
+                .add_sized(
+                    (input_width, 0.0),
+                    egui::TextEdit::singleline(value).hint_text("Value"),
+                )
+                .changed()
+            {
+                *should_update = true;
+            }
+
+            if ui
+                .small_button("🗑")
+                .on_hover_text("Remove this header")
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .clicked()
+            {
+                removed_header = Some(index);
+            }
+        });
+    }
+
+    if let Some(index) = removed_header {
+        request.headers.remove(index);
+
+        *should_update = true;
+    }
+
+    ui.label("Body");
+
+    const ROWS: usize = 2;
+
+    let mut response = None;
+
+    egui::ScrollArea::vertical()
+        .id_salt(format!("{}-body", id_source))
+        .max_height((ROWS + 1) as f32 * 20.0)
+        .show(ui, |ui| {
+            response = Some(
+                ui.add(
+                    egui::TextEdit::multiline(&mut request.body)
+                        .desired_rows(ROWS)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("{ \"value\": {value} }"),
+                ),
+            );
+        });
+
+    if let Some(r) = response {
+        if r.changed() {
+            *should_update = true;
+        }
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Response");
+
+        egui::ComboBox::new(format!("{}-response", id_source), "")
+            .selected_text(match request.response {
+                HttpResponseAction::Log => "Log",
+                HttpResponseAction::Display => "Show on Display",
+            })
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(request.response == HttpResponseAction::Log, "Log")
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    request.response = HttpResponseAction::Log;
+
+                    *should_update = true;
+                }
+
+                if ui
+                    .selectable_label(
+                        request.response == HttpResponseAction::Display,
+                        "Show on Display",
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    request.response = HttpResponseAction::Display;
+
+                    *should_update = true;
+                }
+            })
+            .response
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+    });
+}
+
+fn draw_mqtt_publish_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    (topic, payload, retain): (&mut String, &mut String, &mut bool),
+    default_hint: String,
+    // (bool, &str) -> `true/false`, `hint_text`
+    has_value: (bool, &str), // does the component have a value? e.g. potentiometer has 0-99
+    should_update: &mut bool,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Topic");
+
+        let retain_label_response = ui
+            .add(egui::Label::new("Retain").sense(egui::Sense::click()))
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text("The broker keeps the last message for new subscribers.");
+
+        let retain_switch_response = ui
+            .add(ToggleSwitch::new(*retain, (50.0, 26.0)))
+            .on_hover_text("The broker keeps the last message for new subscribers.");
+
+        if retain_label_response.clicked() || retain_switch_response.clicked() {
+            *retain = !*retain;
+
+            *should_update = true;
+        }
+    });
+
+    if ui
+        .add(
+            egui::TextEdit::singleline(topic)
+                .margin(Vec2::new(8.0, 8.0))
+                .desired_width(f32::INFINITY)
+                .hint_text("home/office/lights"),
+        )
+        .changed()
+    {
+        *should_update = true;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Payload");
+
+        let hint = default_hint
+            + if has_value.0 {
+                "Example:\n\t{ \"brightness\": {value} }"
+            } else {
+                "Example:\n\tTOGGLE"
+            };
+        let hint = hint
+            + if has_value.0 {
+                format!("\n\n({})", has_value.1)
+            } else {
+                String::new()
+            }
+            .as_str();
+
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new("ℹ").color(Color::LIGHT_BLUE.gamma_multiply(0.75)),
+            )
+            .sense(egui::Sense::hover()),
+        )
+        .on_hover_cursor(egui::CursorIcon::Help)
+        .on_hover_text(
+            egui::RichText::new(hint)
+                .color(Color::LIGHT_BLUE)
+                .size(16.0),
+        );
+    });
+
+    const ROWS: usize = 2;
+
+    let mut response = None;
+
+    egui::ScrollArea::vertical()
+        .id_salt(format!("{}-payload", id_source))
+        .max_height((ROWS + 1) as f32 * 20.0)
+        .show(ui, |ui| {
+            response = Some(
+                ui.add(
+                    egui::TextEdit::multiline(payload)
+                        .desired_rows(ROWS)
+                        .desired_width(f32::INFINITY),
+                ),
+            );
+        });
+
+    if let Some(r) = response {
+        if r.changed() {
+            *should_update = true;
+        }
+    }
+}
+
+fn draw_dbus_call_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    call: &mut DBusCall,
+    default_hint: String,
+    // (bool, &str) -> `true/false`, `hint_text`
+    has_value: (bool, &str), // does the component have a value? e.g. potentiometer has 0-99
+    should_update: &mut bool,
+) {
+    let fields = [
+        (
+            "Destination",
+            &mut call.destination,
+            "org.freedesktop.Notifications",
+        ),
+        ("Path", &mut call.path, "/org/freedesktop/Notifications"),
+        (
+            "Interface",
+            &mut call.interface,
+            "org.freedesktop.Notifications",
+        ),
+        ("Method", &mut call.method, "Notify"),
+    ];
+
+    egui::Grid::new(format!("{}-fields", id_source))
+        .num_columns(2)
+        .show(ui, |ui| {
+            for (label, text, hint) in fields {
+                ui.label(label);
+
+                if ui
+                    .add(
+                        egui::TextEdit::singleline(text)
+                            .desired_width(f32::INFINITY)
+                            .hint_text(hint),
+                    )
+                    .changed()
+                {
+                    *should_update = true;
+                }
+
+                ui.end_row();
+            }
+        });
+
+    ui.horizontal(|ui| {
+        ui.label("Arguments");
+
+        let hint = default_hint
+            + "Text arguments can use the component's value as well."
+            + if has_value.0 {
+                format!("\n\n({})", has_value.1)
+            } else {
+                String::new()
+            }
+            .as_str();
+
+        ui.add(
+            egui::Label::new(
+                egui::RichText::new("ℹ").color(Color::LIGHT_BLUE.gamma_multiply(0.75)),
+            )
+            .sense(egui::Sense::hover()),
+        )
+        .on_hover_cursor(egui::CursorIcon::Help)
+        .on_hover_text(
+            egui::RichText::new(hint)
+                .color(Color::LIGHT_BLUE)
+                .size(16.0),
+        );
+
+        if ui
+            .small_button("+")
+            .on_hover_text("Add an argument")
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .clicked()
+        {
+            call.arguments.push(DBusArgument::String(String::new()));
+
+            *should_update = true;
+        }
+    });
+
+    let mut removed_argument = None;
+
+    for (index, argument) in call.arguments.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            let argument_type = match argument {
+                DBusArgument::String(_) => "String",
+                DBusArgument::Boolean(_) => "Boolean",
+                DBusArgument::Int32(_) => "Int32",
+                DBusArgument::UInt32(_) => "UInt32",
+                DBusArgument::Int64(_) => "Int64",
+                DBusArgument::Double(_) => "Double",
+                DBusArgument::ObjectPath(_) => "ObjectPath",
+            };
+
+            egui::ComboBox::new(format!("{}-argument-{}", id_source, index), "")
+                .selected_text(argument_type)
+                .width(100.0)
+                .show_ui(ui, |ui| {
+                    let types = [
+                        ("String", DBusArgument::String(String::new())),
+                        ("Boolean", DBusArgument::Boolean(false)),
+                        ("Int32", DBusArgument::Int32(0)),
+                        ("UInt32", DBusArgument::UInt32(0)),
+                        ("Int64", DBusArgument::Int64(0)),
+                        ("Double", DBusArgument::Double(0.0)),
+                        ("ObjectPath", DBusArgument::ObjectPath("/".to_string())),
+                    ];
+
+                    for (name, default) in types {
+                        if ui
+                            .selectable_label(argument_type == name, name)
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .clicked()
+                        {
+                            *argument = default;
+
+                            *should_update = true;
+                        }
+                    }
+                })
+                .response
+                .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+            let value_width = ui.available_width() - 32.0;
+
+            let response = match argument {
+                DBusArgument::String(text) | DBusArgument::ObjectPath(text) => {
+                    ui.add_sized((value_width, 0.0), egui::TextEdit::singleline(text))
+                }
+                DBusArgument::Boolean(value) => ui.checkbox(value, ""),
+                DBusArgument::Int32(value) => ui.add(DragValue::new(value)),
+                DBusArgument::UInt32(value) => ui.add(DragValue::new(value)),
+                DBusArgument::Int64(value) => ui.add(DragValue::new(value)),
+                DBusArgument::Double(value) => ui.add(DragValue::new(value).speed(0.01)),
+            };
+
+            if response.changed() {
+                *should_update = true;
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .small_button("🗑")
+                    .on_hover_text("Remove this argument")
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    removed_argument = Some(index);
+                }
+            });
+        });
+    }
+
+    if let Some(index) = removed_argument {
+        call.arguments.remove(index);
+
+        *should_update = true;
+    }
+}
+
+fn draw_media_control_fields(
+    ui: &mut Ui,
+    id_source: &str,
+    (action, player, report_now_playing): (&mut MediaAction, &mut String, &mut bool),
+    // (bool, &str) -> `true/false`, `hint_text`
+    has_value: (bool, &str), // does the component have a value? e.g. potentiometer has 0-99
+    should_update: &mut bool,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Action");
+
+        egui::ComboBox::new(format!("{}-action", id_source), "")
+            .selected_text(format!("{}", action))
+            .show_ui(ui, |ui| {
+                let mut actions = vec![
+                    MediaAction::PlayPause,
+                    MediaAction::Play,
+                    MediaAction::Pause,
+                    MediaAction::Stop,
+                    MediaAction::Next,
+                    MediaAction::Previous,
+                    MediaAction::Seek(10),
+                    MediaAction::Volume(5),
+                ];
+
+                // Setting the volume directly only makes sense with a value. e.g. potentiometer
+                if has_value.0 {
+                    actions.push(MediaAction::SetVolume);
+                }
+
+                for option in actions {
+                    let selected =
+                        std::mem::discriminant(action) == std::mem::discriminant(&option);
+
+                    if ui
+                        .selectable_label(selected, format!("{}", option))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                        && !selected
+                    {
+                        *action = option;
+
+                        *should_update = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+        let response = match action {
+            MediaAction::Seek(seconds) => Some(
+                ui.add(DragValue::new(seconds).range(-600..=600).suffix("s"))
+                    .on_hover_text("Negative values seek backward"),
+            ),
+            MediaAction::Volume(percent) => Some(
+                ui.add(DragValue::new(percent).range(-100..=100).suffix("%"))
+                    .on_hover_text("Negative values lower the volume"),
+            ),
+            _ => None,
+        };
+
+        if let Some(r) = response {
+            if r.changed() {
+                *should_update = true;
+            }
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Player");
+
+        if ui
+            .add(
+                egui::TextEdit::singleline(player)
+                    .desired_width(f32::INFINITY)
+                    .hint_text("Most recent player"),
+            )
+            .on_hover_text("Part of the player's name. e.g. spotify, vlc, firefox")
+            .changed()
+        {
+            *should_update = true;
+        }
+    });
+
+    ui.horizontal(|ui| {
+        let report_label_response = ui
+            .add(egui::Label::new("Show Now Playing").sense(egui::Sense::click()))
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text("Show the current track on the device's display.");
+
+        let report_switch_response = ui
+            .add(ToggleSwitch::new(*report_now_playing, (50.0, 26.0)))
+            .on_hover_text("Show the current track on the device's display.");
+
+        if report_label_response.clicked() || report_switch_response.clicked() {
+            *report_now_playing = !*report_now_playing;
+
+            *should_update = true;
+        }
+    });
 }
@@ -1,24 +1,59 @@
-use padpad_software::{constants::SERIAL_MESSAGE_SEP, tcp::client_to_server_message};
+use padpad_software::{
+    service::{interaction::CommandOutput, plugin::PluginDescription},
+    tcp::client_to_server_message,
+};
+use serde_json::{json, Value};
 
-pub fn request_send_serial(message: &str) -> Result<String, String> {
-    let request = format!("send_serial{}{}", SERIAL_MESSAGE_SEP, message);
-
-    client_to_server_message(&request)
+pub fn request_send_serial(message: &str) -> Result<(), String> {
+    client_to_server_message("send_serial", json!({ "message": message }))
+        .map(|_| ())
+        .map_err(|e| e.to_string())
 }
 
 pub fn request_refresh_device() {
     request_send_serial("refresh_device").ok();
 }
 
-pub fn request_device_upload(data: String, save_to_flash: bool) -> Result<String, String> {
+pub fn request_device_upload(data: String, save_to_flash: bool) -> Result<(), String> {
     // `u` => Upload, `M` => Save to Memory
     let request = format!("u{}{}", if save_to_flash { "M" } else { "-" }, data);
 
     request_send_serial(&request)
 }
 
-pub fn request_restart_service() -> Result<String, String> {
-    client_to_server_message("restart")
+pub fn request_command_output(component_global_id: &str) -> Result<CommandOutput, String> {
+    let response = client_to_server_message(
+        "command_output",
+        json!({ "component": component_global_id }),
+    )
+    .map_err(|e| e.to_string())?;
+
+    serde_json::from_value(response).map_err(|e| e.to_string())
+}
+
+pub fn request_plugins() -> Result<Vec<PluginDescription>, String> {
+    let response = client_to_server_message("plugins", Value::Null).map_err(|e| e.to_string())?;
+
+    serde_json::from_value(response).map_err(|e| e.to_string())
+}
+
+pub fn request_simulate(
+    component_global_id: &str,
+    value: &str,
+    modkey: bool,
+) -> Result<(), String> {
+    client_to_server_message(
+        "simulate",
+        json!({ "component": component_global_id, "value": value, "modkey": modkey }),
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+pub fn request_restart_service() -> Result<(), String> {
+    client_to_server_message("restart", Value::Null)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
 }
 
 pub fn blend_colors(
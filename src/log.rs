@@ -2,12 +2,16 @@ use std::{
     fs::OpenOptions,
     io::{self, Write},
     path::Path,
-    sync::{Mutex, OnceLock},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
 };
 
 use crate::{constants::APP_NAME, utility::get_app_name_without_extension};
 
 static LOGGER: OnceLock<Logger> = OnceLock::new();
+static CONSOLE_OUTPUT: AtomicBool = AtomicBool::new(true);
 
 pub struct Logger {
     file: Mutex<std::fs::File>,
@@ -17,6 +21,11 @@ pub fn get_logger() -> &'static Logger {
     init_logger()
 }
 
+/// Only write to the log file, so apps with machine-readable output (`padpadctl`) stay clean
+pub fn disable_console_output() {
+    CONSOLE_OUTPUT.store(false, Ordering::Relaxed);
+}
+
 impl Logger {
     fn new(log_path: &Path) -> io::Result<Self> {
         let file = OpenOptions::new()
@@ -70,7 +79,18 @@ impl Logger {
         }
 
         // Print to console
-        println!("{}", log_message);
+        if CONSOLE_OUTPUT.load(Ordering::Relaxed) {
+            println!("{}", log_message);
+        }
+    }
+}
+
+/// Make sure every log is on the disk, e.g. before the app exits
+pub fn flush_logs() {
+    if let Some(logger) = LOGGER.get() {
+        if let Ok(file) = logger.file.lock() {
+            file.sync_all().ok();
+        }
     }
 }
 
@@ -1,24 +1,31 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use padpad_software::{config, constants::DEBUG_SERIAL_DISABLE, log_error, log_info, service, tcp};
+use padpad_software::{config, constants::DEBUG_SERIAL_DISABLE, log_info, service, tcp};
 
 fn main() {
     log_info!("Application started at {:?}", std::env::current_exe());
 
-    if tcp::is_another_instance_running() {
-        log_error!("Another instance of the app is already running!");
+    // Read configuration or create it if it doesn't exist, it has the IPC endpoint
+    config::init();
 
+    // Lock file and IPC identity check, a second launch talks to the running instance instead
+    if !service::instance::init() {
         return;
     }
 
-    // Read configuration or create it if it doesn't exist
-    config::init();
+    // Secret that the `Dashboard` and other local clients need to control the `Service`
+    tcp::init_ipc_token();
 
     // Initialize an empty Serial object
     service::serial::init();
 
+    // Internal event bus, subscribed before any thread can publish to it
+    let device_commands = service::bus::init_device_commands();
+    let ipc_events = service::bus::subscribe();
+    let interaction_events = service::bus::subscribe();
+
     // Application service tray icon
-    let tray_thread = std::thread::Builder::new()
+    std::thread::Builder::new()
         .name("Tray".to_string())
         .spawn(|| {
             log_info!("Tray thread is started...");
@@ -37,6 +44,66 @@ fn main() {
         })
         .expect("Failed to spawn `TCP Server` thread!");
 
+    // MQTT client for publishing interactions and subscribing to device feedback
+    let mqtt_thread = std::thread::Builder::new()
+        .name("MQTT".to_string())
+        .spawn(|| {
+            log_info!("MQTT thread is started...");
+
+            service::mqtt::handle_mqtt_thread();
+        })
+        .expect("Failed to spawn `MQTT` thread!");
+
+    // REST API and WebSocket for third-party integrations
+    let api_thread = std::thread::Builder::new()
+        .name("HTTP API".to_string())
+        .spawn(|| {
+            log_info!("HTTP API thread is started...");
+
+            service::api::handle_api_thread();
+        })
+        .expect("Failed to spawn `HTTP API` thread!");
+
+    // Plugins are loaded once, they keep running in the background
+    let plugins_thread = std::thread::Builder::new()
+        .name("Plugins".to_string())
+        .spawn(|| {
+            log_info!("Plugins thread is started...");
+
+            service::plugin::load_plugins();
+        })
+        .expect("Failed to spawn `Plugins` thread!");
+
+    // Switches profiles based on the focused window
+    let focus_thread = std::thread::Builder::new()
+        .name("Focus".to_string())
+        .spawn(|| {
+            log_info!("Focus thread is started...");
+
+            service::focus::handle_focus_thread();
+        })
+        .expect("Failed to spawn `Focus` thread!");
+
+    // Switches profiles based on their schedules
+    let schedule_thread = std::thread::Builder::new()
+        .name("Schedule".to_string())
+        .spawn(|| {
+            log_info!("Schedule thread is started...");
+
+            service::schedule::handle_schedule_thread();
+        })
+        .expect("Failed to spawn `Schedule` thread!");
+
+    // Clipboard history for clipboard interactions
+    let clipboard_thread = std::thread::Builder::new()
+        .name("Clipboard".to_string())
+        .spawn(|| {
+            log_info!("Clipboard thread is started...");
+
+            service::clipboard::handle_clipboard_thread();
+        })
+        .expect("Failed to spawn `Clipboard` thread!");
+
     let serial_thread = std::thread::Builder::new()
         .name("Serial".to_string())
         .spawn(|| {
@@ -56,13 +123,48 @@ fn main() {
         })
         .expect("Failed to spawn `Serial` thread!");
 
-    tray_thread
-        .join()
-        .expect_err("there was a problem while spawning the `tray` thread!");
-    tcp_server_thread
-        .join()
-        .expect_err("there was a problem while spawning the `tcp_server` thread!");
-    serial_thread
-        .join()
-        .expect_err("there was a problem while spawning the `tcp_server` thread!");
+    // Messages for the device from other threads are written as soon as they arrive
+    let serial_writer_thread = std::thread::Builder::new()
+        .name("Serial writer".to_string())
+        .spawn(move || {
+            log_info!("Serial writer thread is started...");
+
+            service::serial::handle_serial_writer_thread(device_commands);
+        })
+        .expect("Failed to spawn `Serial writer` thread!");
+
+    // Runs interactions of the components, so slow ones don't block reading from the device
+    let interaction_thread = std::thread::Builder::new()
+        .name("Interaction".to_string())
+        .spawn(move || {
+            log_info!("Interaction thread is started...");
+
+            service::interaction::handle_interaction_thread(interaction_events);
+        })
+        .expect("Failed to spawn `Interaction` thread!");
+
+    // Device, config and interaction events are sent to the subscribed `TCP clients`
+    let ipc_events_thread = std::thread::Builder::new()
+        .name("IPC events".to_string())
+        .spawn(move || {
+            log_info!("IPC events thread is started...");
+
+            tcp::handle_service_events_thread(ipc_events);
+        })
+        .expect("Failed to spawn `IPC events` thread!");
+
+    // Stops every thread once the app is shutting down, the tray stops with the process
+    service::shutdown::handle_shutdown(vec![
+        tcp_server_thread,
+        api_thread,
+        ipc_events_thread,
+        serial_thread,
+        serial_writer_thread,
+        interaction_thread,
+        mqtt_thread,
+        plugins_thread,
+        focus_thread,
+        schedule_thread,
+        clipboard_thread,
+    ]);
 }
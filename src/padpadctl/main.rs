@@ -0,0 +1,347 @@
+use padpad_software::{
+    config::{check_config, Config},
+    constants::{APP_NAME, APP_VERSION, HOME_IMAGE_BYTES_SIZE},
+    log::disable_console_output,
+    tcp::{client_to_server_message, subscribe_to_server, IpcError, ServerEvent},
+    utility::{extract_hex_bytes, hex_bytes_vec_to_string},
+};
+use serde_json::{json, Value};
+
+const USAGE: &str = "Usage: padpadctl [--json] <command>
+
+Commands:
+  status                          Show the state of the `Service` and the device
+  profile list                    List the profiles
+  profile set <name|index>        Switch the current profile
+  trigger <component> [value]     Run a component's interaction. e.g. `Button:1`
+          [--modkey]              Run the alternative (`ModKey`) interaction
+          [--confirm]             Confirm an interaction that requires a confirmation
+  simulate <component> <value>    Handle an event as if it came from the device
+          [--modkey]              e.g. `Button:1 1` (press), `Button:1 0` (release)
+  send-serial <message>           Send a raw message to the device
+  reload                          Reload the config file
+  restart                         Restart the `Service`
+  config export [file]            Write the config to a file or stdout
+  config import <file>            Check and replace the config
+  config validate [file]          Check a config file (default: the `Service`'s config)
+  watch                           Print events until interrupted
+  upload-image <file> [--save]    Upload an X BitMap (.xbm) as the home image
+                                  `--save` keeps it in the device's flash memory
+
+Options:
+  --json                          Machine-readable output
+  -h, --help                      Show this message";
+
+enum CliError {
+    Usage(String),  // Wrong arguments, the usage is printed too
+    Failed(String), // The command itself failed
+}
+
+impl From<IpcError> for CliError {
+    fn from(error: IpcError) -> Self {
+        CliError::Failed(error.to_string())
+    }
+}
+
+struct Options {
+    json: bool,
+    modkey: bool,
+    confirm: bool,
+    save: bool,
+}
+
+fn main() {
+    // Logs still go to the log file, but stdout is only used for the command's output
+    disable_console_output();
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!(
+            "{} {} command-line client\n\n{}",
+            APP_NAME, APP_VERSION, USAGE
+        );
+
+        return;
+    }
+
+    let mut take_flag = |flag: &str| -> bool {
+        let found = args.iter().any(|arg| arg == flag);
+
+        args.retain(|arg| arg != flag);
+
+        found
+    };
+
+    let options = Options {
+        json: take_flag("--json"),
+        modkey: take_flag("--modkey"),
+        confirm: take_flag("--confirm"),
+        save: take_flag("--save"),
+    };
+
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+
+    match run(&args, &options) {
+        Ok(_) => (),
+        Err(CliError::Usage(message)) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+
+            std::process::exit(2);
+        }
+        Err(CliError::Failed(message)) => {
+            if options.json {
+                println!("{}", json!({ "error": message }));
+            } else {
+                eprintln!("Error: {}", message);
+            }
+
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(args: &[&str], options: &Options) -> Result<(), CliError> {
+    match args {
+        ["status"] => {
+            let status = client_to_server_message("status", Value::Null)?;
+
+            print_result(options, &status, || {
+                format!(
+                    "Service:  running ({}) v{}\nDevice:   {}\nProfile:  {} ({})",
+                    status["endpoint"].as_str().unwrap_or_default(),
+                    status["app"].as_str().unwrap_or_default(),
+                    if status["is_device_paired"].as_bool().unwrap_or(false) {
+                        format!(
+                            "paired (firmware {})",
+                            status["firmware_version"].as_str().unwrap_or("unknown")
+                        )
+                    } else {
+                        "not paired".to_string()
+                    },
+                    status["profile"]["name"].as_str().unwrap_or_default(),
+                    status["profile"]["index"]
+                )
+            });
+        }
+        ["profile", "list"] => {
+            let profiles = client_to_server_message("profiles", Value::Null)?;
+
+            print_result(options, &profiles, || {
+                profiles
+                    .as_array()
+                    .map(|profiles| {
+                        profiles
+                            .iter()
+                            .map(|profile| {
+                                format!(
+                                    "{} {:>2}  {}",
+                                    if profile["current"].as_bool().unwrap_or(false) {
+                                        "*"
+                                    } else {
+                                        " "
+                                    },
+                                    profile["index"],
+                                    profile["name"].as_str().unwrap_or_default()
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default()
+            });
+        }
+        ["profile", "set", profile] => {
+            let result = client_to_server_message("switch_profile", json!({ "profile": profile }))?;
+
+            print_result(options, &result, || {
+                format!("Switched to profile `{}`.", profile)
+            });
+        }
+        ["trigger", component, rest @ ..] if rest.len() <= 1 => {
+            let result = client_to_server_message(
+                "trigger",
+                json!({
+                    "component": component,
+                    "value": rest.first(),
+                    "modkey": options.modkey,
+                    "confirm": options.confirm,
+                }),
+            )?;
+
+            print_result(options, &result, || format!("Triggered `{}`.", component));
+        }
+        ["simulate", component, value] => {
+            let result = client_to_server_message(
+                "simulate",
+                json!({
+                    "component": component,
+                    "value": value,
+                    "modkey": options.modkey,
+                }),
+            )?;
+
+            print_result(options, &result, || {
+                format!("Simulated `{}` = `{}`.", component, value)
+            });
+        }
+        ["send-serial", message] => {
+            let result = client_to_server_message("send_serial", json!({ "message": message }))?;
+
+            print_result(options, &result, || "Message was queued.".to_string());
+        }
+        ["reload"] => {
+            let result = client_to_server_message("reload_config", Value::Null)?;
+
+            print_result(options, &result, || "Config was reloaded.".to_string());
+        }
+        ["restart"] => {
+            let result = client_to_server_message("restart", Value::Null)?;
+
+            print_result(options, &result, || "Service is restarting...".to_string());
+        }
+        ["config", "export", rest @ ..] if rest.len() <= 1 => {
+            let config = client_to_server_message("config_export", Value::Null)?;
+            let config = config.as_str().unwrap_or_default();
+
+            match rest.first() {
+                Some(path) => {
+                    std::fs::write(path, config).map_err(|e| CliError::Failed(e.to_string()))?;
+
+                    print_result(options, &json!({ "path": path }), || {
+                        format!("Config was exported to `{}`.", path)
+                    });
+                }
+                None if options.json => println!("{}", json!({ "config": config })),
+                None => print!("{}", config),
+            }
+        }
+        ["config", "import", path] => {
+            let config = read_file(path)?;
+
+            // Checked here first, so the errors are shown even if the `Service` isn't running
+            check_config(&config).map_err(|errors| CliError::Failed(errors.join("\n")))?;
+
+            let result = client_to_server_message("config_import", json!({ "config": config }))?;
+
+            print_result(options, &result, || {
+                format!("Config was imported from `{}`.", path)
+            });
+        }
+        ["config", "validate", rest @ ..] if rest.len() <= 1 => {
+            // The `Service` knows which file it uses, the same lookup is done if it isn't running
+            let path = match rest.first() {
+                Some(path) => path.to_string(),
+                None => client_to_server_message("status", Value::Null)
+                    .ok()
+                    .and_then(|status| status["config_file"].as_str().map(String::from))
+                    .unwrap_or_else(|| Config::default().find_file_path()),
+            };
+
+            let errors = match check_config(&read_file(&path)?) {
+                Ok(_) => vec![],
+                Err(errors) => errors,
+            };
+
+            print_result(
+                options,
+                &json!({ "valid": errors.is_empty(), "errors": errors }),
+                || {
+                    if errors.is_empty() {
+                        format!("`{}` is valid.", path)
+                    } else {
+                        format!("`{}` is invalid:\n- {}", path, errors.join("\n- "))
+                    }
+                },
+            );
+
+            if !errors.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        ["watch"] => {
+            subscribe_to_server(|event| {
+                if options.json {
+                    println!(
+                        "{}",
+                        serde_json::to_string(&event).unwrap_or("{}".to_string())
+                    );
+
+                    return;
+                }
+
+                match event {
+                    ServerEvent::State(data) => println!(
+                        "Device {}",
+                        if data.is_device_paired {
+                            "paired"
+                        } else {
+                            "not paired"
+                        }
+                    ),
+                    ServerEvent::Component(component_global_id, value) => {
+                        println!("{} = {}", component_global_id, value)
+                    }
+                    ServerEvent::Profile(index, name) => {
+                        println!("Profile switched to `{}` ({})", name, index)
+                    }
+                    ServerEvent::ReloadConfig() => println!("Config was reloaded"),
+                    ServerEvent::CommandOutput(component_global_id, output) => println!(
+                        "{} command `{}` finished: {}",
+                        component_global_id, output.command, output.status
+                    ),
+                }
+            })?;
+        }
+        ["upload-image", path] => {
+            let image = read_file(path)?;
+
+            let bytes =
+                extract_hex_bytes(&image, HOME_IMAGE_BYTES_SIZE).map_err(CliError::Failed)?;
+
+            let status = client_to_server_message("status", Value::Null)?;
+
+            if !status["is_device_paired"].as_bool().unwrap_or(false) {
+                return Err(CliError::Failed("Device isn't paired!".to_string()));
+            }
+
+            // `u` => Upload, `M` => Save to Memory, `i` => *HOME* Image
+            let message = format!(
+                "u{}i{}",
+                if options.save { "M" } else { "-" },
+                hex_bytes_vec_to_string(&bytes)
+            );
+
+            let result = client_to_server_message("send_serial", json!({ "message": message }))?;
+
+            print_result(options, &result, || {
+                "Home image was uploaded to the device.".to_string()
+            });
+        }
+        [] => return Err(CliError::Usage("Missing command!".to_string())),
+        _ => {
+            return Err(CliError::Usage(format!(
+                "Unknown command or wrong arguments: `{}`",
+                args.join(" ")
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+fn print_result<F>(options: &Options, result: &Value, human: F)
+where
+    F: FnOnce() -> String,
+{
+    if options.json {
+        println!("{}", result);
+    } else {
+        println!("{}", human());
+    }
+}
+
+fn read_file(path: &str) -> Result<String, CliError> {
+    std::fs::read_to_string(path)
+        .map_err(|e| CliError::Failed(format!("Could not read `{}`: {}", path, e)))
+}
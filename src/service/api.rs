@@ -0,0 +1,685 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::RecvTimeoutError,
+    },
+    time::{Duration, Instant},
+};
+
+use base64::Engine;
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+
+use crate::{
+    config::{HttpApiSettings, CONFIG},
+    constants::{
+        HTTP_API_MAX_BODY_SIZE, HTTP_API_MAX_CONNECTIONS, HTTP_API_MAX_HEADERS,
+        HTTP_API_MAX_LINE_LENGTH, HTTP_API_READ_TIMEOUT, IPC_SUBSCRIBER_WRITE_TIMEOUT,
+        SERVER_ACCEPT_INTERVAL, WEBSOCKET_PING_INTERVAL,
+    },
+    log_error, log_info, log_warn,
+    service::{interaction::get_all_component_values, shutdown::is_shutting_down},
+    tcp::{
+        handle_local_request, is_secret_equal, is_token_valid, subscribe_events, DeadlineReader,
+        IpcError,
+    },
+};
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+static CONNECTIONS: AtomicUsize = AtomicUsize::new(0); // Connections that are being handled
+
+/// Counts a connection until it's dropped
+struct ConnectionGuard;
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>, // Names are lowercase
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+/// Opt-in REST API and WebSocket for third-party integrations. e.g. stream overlays
+pub fn handle_api_thread() {
+    let settings = {
+        let config = CONFIG
+            .get()
+            .expect("Could not retrieve CONFIG data!")
+            .lock()
+            .unwrap();
+
+        config.settings.http_api.clone()
+    };
+
+    if !settings.enabled {
+        log_info!("HTTP API is disabled in the config file.");
+
+        return;
+    }
+
+    let listener = match TcpListener::bind(&settings.address) {
+        Ok(l) => l,
+        Err(e) => {
+            log_error!("HTTP API could not bind to `{}`: {}", settings.address, e);
+
+            return;
+        }
+    };
+
+    if listener
+        .local_addr()
+        .map(|address| !address.ip().is_loopback())
+        .unwrap_or(false)
+    {
+        log_warn!(
+            "HTTP API is reachable from other devices on `{}`, keep its token secret!",
+            settings.address
+        );
+    }
+
+    log_info!("HTTP API is running on http://{}", settings.address);
+
+    // Doesn't block, so the server stops once the app is shutting down
+    if let Err(e) = listener.set_nonblocking(true) {
+        log_error!("HTTP API could not be set to non-blocking: {}", e);
+
+        return;
+    }
+
+    while !is_shutting_down() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                // Dropping the stream closes it, so a flood of clients can't start endless threads
+                if CONNECTIONS.fetch_add(1, Ordering::SeqCst) >= HTTP_API_MAX_CONNECTIONS {
+                    CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+
+                    log_warn!("HTTP API has too many connections, closing a new one...");
+
+                    continue;
+                }
+
+                let connection_guard = ConnectionGuard;
+
+                stream.set_nonblocking(false).ok();
+
+                let settings = settings.clone();
+
+                std::thread::spawn(move || {
+                    let _connection_guard = connection_guard;
+
+                    handle_connection(stream, &settings)
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(SERVER_ACCEPT_INTERVAL));
+            }
+            Err(_) => (),
+        }
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, settings: &HttpApiSettings) {
+    let request = match read_request(&mut stream) {
+        Ok(r) => r,
+        Err(e) => {
+            write_response(&mut stream, Response::error(400, &e), None);
+
+            return;
+        }
+    };
+
+    // Browsers always send it for WebSockets and requests from other websites
+    let origin = request.headers.get("origin").map(|origin| origin.as_str());
+
+    if !is_origin_allowed(origin, settings) {
+        write_response(
+            &mut stream,
+            Response::error(403, "Origin is not allowed!"),
+            None,
+        );
+
+        return;
+    }
+
+    // Browsers ask before sending JSON or an `Authorization` header to another origin, and they
+    // don't send the token with the question
+    if request.method == "OPTIONS" {
+        write_preflight_response(&mut stream, origin);
+
+        return;
+    }
+
+    if !is_authorized(&request, settings) {
+        write_response(
+            &mut stream,
+            Response::error(401, "Invalid or missing token!"),
+            origin,
+        );
+
+        return;
+    }
+
+    if request.method == "GET" && request.path == "/api/events" {
+        return handle_websocket(stream, &request);
+    }
+
+    // Browsers can't send JSON to another origin without asking first (which is only answered
+    // for allowed origins), so other websites can't trigger interactions
+    if request.method == "POST"
+        && !request
+            .headers
+            .get("content-type")
+            .is_some_and(|content_type| content_type.starts_with("application/json"))
+    {
+        write_response(
+            &mut stream,
+            Response::error(415, "`Content-Type` must be `application/json`!"),
+            origin,
+        );
+
+        return;
+    }
+
+    let response = handle_request(&request);
+
+    write_response(&mut stream, response, origin);
+}
+
+fn handle_request(request: &Request) -> Response {
+    let body: Value = if request.body.is_empty() {
+        json!({})
+    } else {
+        match serde_json::from_slice(&request.body) {
+            Ok(b) => b,
+            Err(e) => return Response::error(400, &format!("Invalid JSON: {}", e)),
+        }
+    };
+
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+
+    let result = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["api", "status"]) => handle_local_request("status", Value::Null),
+        ("GET", ["api", "profiles"]) => handle_local_request("profiles", Value::Null),
+        ("POST", ["api", "profiles", "current"]) => {
+            // Profiles can be selected by their index too
+            let profile = match &body["profile"] {
+                Value::Number(index) => index.to_string(),
+                profile => profile.as_str().unwrap_or_default().to_string(),
+            };
+
+            handle_local_request("switch_profile", json!({ "profile": profile }))
+        }
+        ("GET", ["api", "layout"]) => {
+            let config = CONFIG
+                .get()
+                .expect("Could not retrieve CONFIG data!")
+                .lock()
+                .unwrap();
+
+            serde_json::to_value(&config.layout).map_err(|e| IpcError::Internal(e.to_string()))
+        }
+        ("GET", ["api", "components"]) => serde_json::to_value(get_all_component_values())
+            .map_err(|e| IpcError::Internal(e.to_string())),
+        ("POST", ["api", "components", component_global_id, "trigger"]) => handle_local_request(
+            "trigger",
+            json!({
+                "component": percent_decode(component_global_id),
+                "value": body["value"],
+                "modkey": body["modkey"],
+                "confirm": body["confirm"],
+            }),
+        ),
+        _ => return Response::error(404, "Not found!"),
+    };
+
+    match result {
+        Ok(body) => Response::ok(body),
+        Err(error) => Response::error(
+            match error {
+                IpcError::InvalidParams(_) | IpcError::Parse(_) => 400,
+                IpcError::Unauthorized(_) => 401,
+                IpcError::MethodNotFound(_) => 404,
+                _ => 500,
+            },
+            error.message(),
+        ),
+    }
+}
+
+/// Stream every `ServerEvent` as a text message until the client disconnects
+fn handle_websocket(mut stream: TcpStream, request: &Request) {
+    let key = match request.headers.get("sec-websocket-key") {
+        Some(k)
+            if request.headers.get("upgrade").map(|u| u.to_lowercase())
+                == Some("websocket".to_string()) =>
+        {
+            k
+        }
+        _ => {
+            write_response(
+                &mut stream,
+                Response::error(400, "Expected a WebSocket connection!"),
+                None,
+            );
+
+            return;
+        }
+    };
+
+    let accept = base64::engine::general_purpose::STANDARD.encode(Sha1::digest(format!(
+        "{}{}",
+        key.trim(),
+        WEBSOCKET_GUID
+    )));
+
+    let handshake = format!(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+        Upgrade: websocket\r\n\
+        Connection: Upgrade\r\n\
+        Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    );
+
+    if stream.write_all(handshake.as_bytes()).is_err() {
+        return;
+    }
+
+    // A stalled tab would block this thread forever otherwise
+    stream
+        .set_write_timeout(Some(Duration::from_millis(IPC_SUBSCRIBER_WRITE_TIMEOUT)))
+        .ok();
+
+    let events = subscribe_events();
+
+    loop {
+        let frame = match events.recv_timeout(Duration::from_millis(WEBSOCKET_PING_INTERVAL)) {
+            // `0x1` => Text
+            Ok(event) => websocket_frame(0x1, &serde_json::to_vec(&event).unwrap_or_default()),
+            // `0x9` => Ping, finds out if the client is gone while there are no events
+            Err(RecvTimeoutError::Timeout) => websocket_frame(0x9, &[]),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        if stream.write_all(&frame).is_err() {
+            break;
+        }
+    }
+}
+
+/// Server frames aren't masked
+fn websocket_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode]; // `0x80` => Final frame
+
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+
+    frame.extend_from_slice(payload);
+
+    frame
+}
+
+/// The whole request has to arrive within `HTTP_API_READ_TIMEOUT`, not each read
+fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(DeadlineReader {
+        stream: &*stream,
+        deadline: Some(Instant::now() + Duration::from_millis(HTTP_API_READ_TIMEOUT)),
+    });
+
+    let line =
+        read_line_limited(&mut reader).map_err(|e| format!("Could not read the request: {}", e))?;
+
+    let mut parts = line.split_whitespace();
+
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Err("Invalid request line!".to_string()),
+    };
+
+    let mut headers = HashMap::new();
+
+    loop {
+        let header = read_line_limited(&mut reader)
+            .map_err(|e| format!("Could not read the headers: {}", e))?;
+
+        let header = header.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if headers.len() >= HTTP_API_MAX_HEADERS {
+            return Err("Too many headers!".to_string());
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    if content_length > HTTP_API_MAX_BODY_SIZE {
+        return Err("Request body is too large!".to_string());
+    }
+
+    let mut body = vec![0; content_length];
+
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| format!("Could not read the body: {}", e))?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(name, value)| (percent_decode(name), percent_decode(value)))
+        .collect();
+
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query,
+        headers,
+        body,
+    })
+}
+
+/// A line can't grow forever, e.g. a client that never sends a line break
+fn read_line_limited(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut line = String::new();
+
+    reader
+        .by_ref()
+        .take(HTTP_API_MAX_LINE_LENGTH as u64)
+        .read_line(&mut line)
+        .map_err(|e| e.to_string())?;
+
+    if line.len() >= HTTP_API_MAX_LINE_LENGTH && !line.ends_with('\n') {
+        return Err("Line is too long!".to_string());
+    }
+
+    Ok(line)
+}
+
+/// Clients without an `Origin` aren't browsers, other websites can't use the API by default
+fn is_origin_allowed(origin: Option<&str>, settings: &HttpApiSettings) -> bool {
+    let origin = match origin {
+        Some(o) => o,
+        None => return true,
+    };
+
+    if settings
+        .allowed_origins
+        .iter()
+        .any(|allowed| allowed.trim_end_matches('/') == origin)
+    {
+        return true;
+    }
+
+    let host = match origin
+        .strip_prefix("http://")
+        .or(origin.strip_prefix("https://"))
+    {
+        Some(h) => h,
+        None => return false,
+    };
+
+    // Without the port. e.g. `localhost:3000` or `[::1]:3000`
+    let host = match host.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => host,
+    };
+
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// `Authorization: Bearer <token>`, or `?token=<token>` for WebSockets in browsers
+fn is_authorized(request: &Request, settings: &HttpApiSettings) -> bool {
+    let token = request
+        .headers
+        .get("authorization")
+        .and_then(|authorization| authorization.strip_prefix("Bearer "))
+        .or(request.query.get("token").map(|token| token.as_str()));
+
+    match token {
+        // Only the current user can read the IPC token
+        Some(token) if settings.token.is_empty() => is_token_valid(Some(token)),
+        Some(token) => is_secret_equal(&settings.token, token),
+        None => false,
+    }
+}
+
+/// `origin` is only set for requests from allowed origins, so browsers can read the response
+fn write_response(stream: &mut TcpStream, response: Response, origin: Option<&str>) {
+    let body = response.body.to_string();
+
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    };
+
+    let cors = match origin {
+        Some(origin) => format!(
+            "Access-Control-Allow-Origin: {}\r\nVary: Origin\r\n",
+            origin
+        ),
+        None => String::new(),
+    };
+
+    let message = format!(
+        "HTTP/1.1 {} {}\r\n\
+        Content-Type: application/json\r\n\
+        Content-Length: {}\r\n\
+        {}\
+        Connection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        body.len(),
+        cors,
+        body
+    );
+
+    stream.write_all(message.as_bytes()).ok();
+}
+
+/// Answers a CORS preflight, `origin` is only set for allowed origins
+fn write_preflight_response(stream: &mut TcpStream, origin: Option<&str>) {
+    let cors = match origin {
+        Some(origin) => format!(
+            "Access-Control-Allow-Origin: {}\r\n\
+            Access-Control-Allow-Methods: GET, POST\r\n\
+            Access-Control-Allow-Headers: Authorization, Content-Type\r\n\
+            Access-Control-Max-Age: 600\r\n\
+            Vary: Origin\r\n",
+            origin
+        ),
+        None => String::new(),
+    };
+
+    let message = format!(
+        "HTTP/1.1 204 No Content\r\n\
+        {}\
+        Connection: close\r\n\r\n",
+        cors
+    );
+
+    stream.write_all(message.as_bytes()).ok();
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                output.push(byte);
+                index += 3;
+            }
+            None => {
+                output.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&output).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> HttpApiSettings {
+        HttpApiSettings {
+            token: "secret".to_string(),
+            allowed_origins: vec!["https://overlay.example.com/".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn allows_local_and_listed_origins() {
+        let settings = settings();
+
+        for origin in [
+            None,
+            Some("http://localhost"),
+            Some("http://localhost:3000"),
+            Some("https://127.0.0.1:8443"),
+            Some("http://[::1]:3000"),
+            Some("https://overlay.example.com"),
+        ] {
+            assert!(is_origin_allowed(origin, &settings), "{:?}", origin);
+        }
+
+        for origin in [
+            "https://example.com",
+            "http://localhost.example.com",
+            "http://localhost@example.com",
+            "http://[::1].example.com",
+            "file://localhost",
+            "null",
+        ] {
+            assert!(!is_origin_allowed(Some(origin), &settings), "{}", origin);
+        }
+    }
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(percent_decode("Button%3A1"), "Button:1");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("a+b"), "a+b");
+    }
+
+    #[test]
+    fn builds_websocket_frames() {
+        assert_eq!(websocket_frame(0x9, &[]), vec![0x89, 0]);
+        assert_eq!(websocket_frame(0x1, b"hi"), vec![0x81, 2, b'h', b'i']);
+
+        let frame = websocket_frame(0x1, &[0; 126]);
+
+        assert_eq!(frame[..4], [0x81, 126, 0, 126]);
+        assert_eq!(frame.len(), 4 + 126);
+
+        let frame = websocket_frame(0x1, &[0; 65_536]);
+
+        assert_eq!(frame[..10], [0x81, 127, 0, 0, 0, 0, 0, 1, 0, 0]);
+        assert_eq!(frame.len(), 10 + 65_536);
+    }
+
+    #[test]
+    fn answers_preflight_for_allowed_origins() {
+        let preflight = |origin: &str| {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            let (stream, _) = listener.accept().unwrap();
+
+            client
+                .write_all(
+                    format!(
+                        "OPTIONS /api/profiles/current HTTP/1.1\r\n\
+                        Origin: {}\r\n\
+                        Access-Control-Request-Method: POST\r\n\
+                        Access-Control-Request-Headers: authorization, content-type\r\n\r\n",
+                        origin
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+
+            handle_connection(stream, &settings());
+
+            let mut response = String::new();
+            client.read_to_string(&mut response).unwrap();
+
+            response
+        };
+
+        let response = preflight("https://overlay.example.com");
+
+        assert!(response.starts_with("HTTP/1.1 204 No Content\r\n"));
+        assert!(response.contains("Access-Control-Allow-Origin: https://overlay.example.com\r\n"));
+        assert!(response.contains("Access-Control-Allow-Headers: Authorization, Content-Type\r\n"));
+
+        let response = preflight("https://example.com");
+
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+        assert!(!response.contains("Access-Control-Allow-Origin"));
+    }
+}
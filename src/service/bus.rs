@@ -0,0 +1,112 @@
+use std::sync::{mpsc, Mutex};
+
+use crate::{
+    config::ComponentKind,
+    log_warn,
+    service::{interaction::CommandOutput, shutdown::is_shutting_down},
+};
+
+static SUBSCRIBERS: Mutex<Vec<mpsc::Sender<ServiceEvent>>> = Mutex::new(vec![]);
+// Only the `Serial writer` reads them
+static DEVICE_COMMANDS: Mutex<Option<mpsc::Sender<DeviceCommand>>> = Mutex::new(None);
+
+/// Everything that happens inside the `Service`, every subscriber gets every event in order
+#[derive(Debug, Clone)]
+pub enum ServiceEvent {
+    Device(DeviceEvent),
+    Config(ConfigEvent),
+    Interaction(InteractionEvent),
+}
+
+#[derive(Debug, Clone)]
+pub enum DeviceEvent {
+    Paired(bool),
+    FirmwareVersion(String),
+    RawButtons(String),        // Buttons that are saved in the device's memory
+    RawPotentiometers(String), // Potentiometers that are saved in the device's memory
+    Input(
+        ComponentKind,
+        u8,     /* id */
+        String, /* value */
+        bool,   /* modkey */
+    ), // A component was used on the device, or it was simulated
+}
+
+#[derive(Debug, Clone)]
+pub enum ConfigEvent {
+    Changed(), // Saved by the `Service`, clients should reload it
+    ProfileSwitched(usize /* index */, String /* name */),
+}
+
+#[derive(Debug, Clone)]
+pub enum InteractionEvent {
+    ComponentValue(
+        String, /* component_global_id */
+        String, /* value */
+    ),
+    CommandFinished(String /* component_global_id */, CommandOutput),
+}
+
+/// Messages for the device, written by the `Serial writer` thread as soon as they arrive
+#[derive(Debug, Clone)]
+pub enum DeviceCommand {
+    Message(String), // Raw serial message. e.g. `l1|255`
+    Refresh(),       // Send the time, date and profiles again
+}
+
+/// Receive every event from now on, subscribe before starting the threads that publish
+pub fn subscribe() -> mpsc::Receiver<ServiceEvent> {
+    let (sender, receiver) = mpsc::channel();
+
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.push(sender);
+    }
+
+    receiver
+}
+
+/// Send an event to every subscriber, never blocks
+pub fn publish(event: ServiceEvent) {
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+}
+
+/// Create the channel for `DeviceCommand`s, the receiver belongs to the `Serial writer` thread
+pub fn init_device_commands() -> mpsc::Receiver<DeviceCommand> {
+    let (sender, receiver) = mpsc::channel();
+
+    if let Ok(mut device_commands) = DEVICE_COMMANDS.lock() {
+        *device_commands = Some(sender);
+    }
+
+    receiver
+}
+
+pub fn send_to_device(command: DeviceCommand) {
+    let device_commands = match DEVICE_COMMANDS.lock() {
+        Ok(d) => d,
+        Err(_) => return,
+    };
+
+    match device_commands.as_ref() {
+        Some(sender) => {
+            sender.send(command).ok();
+        }
+        None if is_shutting_down() => (),
+        None => {
+            log_warn!("`{:?}` was dropped, device commands aren't ready!", command);
+        }
+    }
+}
+
+/// Drop every sender, so the threads that receive from the bus return
+pub fn close() {
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.clear();
+    }
+
+    if let Ok(mut device_commands) = DEVICE_COMMANDS.lock() {
+        *device_commands = None;
+    }
+}
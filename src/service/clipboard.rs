@@ -0,0 +1,276 @@
+use std::{
+    collections::VecDeque,
+    io::Write,
+    process::{Command, Stdio},
+    sync::{Mutex, OnceLock},
+};
+
+use crate::{
+    config::CONFIG,
+    constants::{CLIPBOARD_HISTORY_SIZE, CLIPBOARD_POLL_INTERVAL, CLIPBOARD_RESTORE_DELAY},
+    log_error, log_info,
+    service::{
+        interaction::{simulate_shortcut, ClipboardAction, InteractionKind},
+        serial::show_on_display,
+        shutdown::is_shutting_down,
+    },
+    utility::EnigoKey,
+};
+
+static CLIPBOARD: OnceLock<Mutex<Option<Box<dyn ClipboardBackend>>>> = OnceLock::new();
+static CLIPBOARD_HISTORY: OnceLock<Mutex<ClipboardHistory>> = OnceLock::new();
+
+/// Access to the system clipboard, implemented for each display server
+pub trait ClipboardBackend: Send {
+    fn get_text(&mut self) -> Result<String, String>;
+    fn set_text(&mut self, text: &str) -> Result<(), String>;
+}
+
+/// X11, Windows and macOS
+struct ArboardBackend(arboard::Clipboard);
+
+/// Wayland, requires `wl-clipboard` to be installed
+struct WaylandBackend;
+
+#[derive(Default)]
+struct ClipboardHistory {
+    entries: VecDeque<String>, // Newest entry first
+    position: usize,           // Entry that was selected by the last cycle
+    last_seen: String,         // Last content of the clipboard, to detect changes
+}
+
+impl ClipboardBackend for ArboardBackend {
+    fn get_text(&mut self) -> Result<String, String> {
+        self.0.get_text().map_err(|e| e.to_string())
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        self.0.set_text(text).map_err(|e| e.to_string())
+    }
+}
+
+impl ClipboardBackend for WaylandBackend {
+    fn get_text(&mut self) -> Result<String, String> {
+        let output = Command::new("wl-paste")
+            .arg("--no-newline")
+            .output()
+            .map_err(|e| format!("Could not run `wl-paste`: {}", e))?;
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        let mut process = Command::new("wl-copy")
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Could not run `wl-copy`: {}", e))?;
+
+        if let Some(mut stdin) = process.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|e| e.to_string())?;
+        }
+
+        // `wl-copy` forks to serve the clipboard in the background
+        process.wait().map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
+
+impl ClipboardHistory {
+    fn record(&mut self, text: String) {
+        if text.trim().is_empty() || self.entries.front() == Some(&text) {
+            return;
+        }
+
+        self.entries.retain(|entry| *entry != text);
+        self.entries.push_front(text);
+        self.entries.truncate(CLIPBOARD_HISTORY_SIZE);
+
+        self.position = 0;
+    }
+}
+
+// Clipboard actions are run on a separate thread, so delays don't block serial
+pub fn do_clipboard_action(action: ClipboardAction) {
+    let spawn_result = std::thread::Builder::new()
+        .name("Clipboard".to_string())
+        .spawn(move || {
+            let result = match &action {
+                ClipboardAction::Copy(snippet) => set_clipboard(snippet, true),
+                ClipboardAction::Paste(snippet, restore) => paste_snippet(snippet, *restore),
+                ClipboardAction::History(paste) => cycle_history(*paste),
+            };
+
+            if let Err(e) = result {
+                log_error!("Clipboard action `{}` failed: {}", action, e);
+            }
+        });
+
+    if let Err(e) = spawn_result {
+        log_error!("Failed to spawn `Clipboard` thread: {}", e);
+    }
+}
+
+/// Records the clipboard's history, only while a profile has a `History` interaction
+pub fn handle_clipboard_thread() {
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(CLIPBOARD_POLL_INTERVAL));
+
+        if is_shutting_down() {
+            return;
+        }
+
+        if !is_history_used() {
+            continue;
+        }
+
+        let text = match get_clipboard() {
+            Ok(t) => t,
+            Err(_) => continue,
+        };
+
+        if let Ok(mut history) = get_clipboard_history().lock() {
+            if history.last_seen != text {
+                history.last_seen = text.clone();
+                history.record(text);
+            }
+        }
+    }
+}
+
+fn paste_snippet(snippet: &str, restore: bool) -> Result<(), String> {
+    let previous = if restore { get_clipboard().ok() } else { None };
+
+    set_clipboard(snippet, !restore)?;
+
+    // Give the focused application a moment to see the new content
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    simulate_paste();
+
+    if let Some(previous) = previous {
+        std::thread::sleep(std::time::Duration::from_millis(CLIPBOARD_RESTORE_DELAY));
+
+        set_clipboard(&previous, false)?;
+    }
+
+    log_info!("Snippet was pasted.");
+
+    Ok(())
+}
+
+/// Each call selects the next (older) entry of the history
+fn cycle_history(paste: bool) -> Result<(), String> {
+    let entry = {
+        let mut history = get_clipboard_history().lock().map_err(|e| e.to_string())?;
+
+        if history.entries.is_empty() {
+            return Err("Clipboard history is empty!".to_string());
+        }
+
+        history.position = (history.position + 1) % history.entries.len();
+
+        history.entries[history.position].clone()
+    };
+
+    set_clipboard(&entry, false)?;
+
+    show_on_display(&entry);
+
+    if paste {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        simulate_paste();
+    }
+
+    Ok(())
+}
+
+fn simulate_paste() {
+    let modifier = if cfg!(target_os = "macos") {
+        enigo::Key::Meta
+    } else {
+        enigo::Key::Control
+    };
+
+    simulate_shortcut(
+        &[EnigoKey(modifier), EnigoKey(enigo::Key::Unicode('v'))],
+        "",
+    );
+}
+
+fn get_clipboard() -> Result<String, String> {
+    with_backend(|backend| backend.get_text())
+}
+
+fn set_clipboard(text: &str, remember: bool) -> Result<(), String> {
+    with_backend(|backend| backend.set_text(text))?;
+
+    if let Ok(mut history) = get_clipboard_history().lock() {
+        history.last_seen = text.to_string();
+
+        if remember {
+            history.record(text.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+fn with_backend<T, F>(callback: F) -> Result<T, String>
+where
+    F: FnOnce(&mut Box<dyn ClipboardBackend>) -> Result<T, String>,
+{
+    let mut clipboard = CLIPBOARD
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .map_err(|e| e.to_string())?;
+
+    // The backend is created on first use, and again if it failed before
+    if clipboard.is_none() {
+        *clipboard = Some(create_backend()?);
+    }
+
+    match clipboard.as_mut() {
+        Some(backend) => callback(backend),
+        None => Err("Clipboard isn't available!".to_string()),
+    }
+}
+
+fn create_backend() -> Result<Box<dyn ClipboardBackend>, String> {
+    if cfg!(target_os = "linux") && std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return Ok(Box::new(WaylandBackend));
+    }
+
+    arboard::Clipboard::new()
+        .map(|clipboard| Box::new(ArboardBackend(clipboard)) as Box<dyn ClipboardBackend>)
+        .map_err(|e| e.to_string())
+}
+
+fn is_history_used() -> bool {
+    let config = CONFIG
+        .get()
+        .expect("Could not retrieve CONFIG data!")
+        .lock()
+        .unwrap();
+
+    config.profiles.iter().any(|profile| {
+        profile.interactions.values().any(|interaction| {
+            [&interaction.normal, &interaction.modkey]
+                .iter()
+                .flat_map(|kind| kind.kinds())
+                .any(|kind| {
+                    matches!(
+                        kind,
+                        InteractionKind::Clipboard(ClipboardAction::History(_))
+                    )
+                })
+        })
+    })
+}
+
+fn get_clipboard_history() -> &'static Mutex<ClipboardHistory> {
+    CLIPBOARD_HISTORY.get_or_init(|| Mutex::new(ClipboardHistory::default()))
+}
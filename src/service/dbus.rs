@@ -0,0 +1,322 @@
+use crate::service::interaction::{DBusCall, MediaAction};
+
+#[cfg(target_os = "linux")]
+use std::sync::{Mutex, OnceLock};
+
+#[cfg(target_os = "linux")]
+use zbus::{
+    blocking::Connection,
+    zvariant::{ObjectPath, OwnedValue, StructureBuilder, Value},
+};
+
+#[cfg(target_os = "linux")]
+use crate::{
+    log_error, log_info,
+    service::{interaction::DBusArgument, serial::show_on_display},
+};
+
+#[cfg(target_os = "linux")]
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+#[cfg(target_os = "linux")]
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+#[cfg(target_os = "linux")]
+const MPRIS_PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+// Last player that was seen playing, used when no player is chosen
+#[cfg(target_os = "linux")]
+static LAST_PLAYER: OnceLock<Mutex<String>> = OnceLock::new();
+
+// Calls are made on a separate thread, so an unresponsive D-Bus service doesn't block serial
+#[cfg(target_os = "linux")]
+pub fn call_method(call: DBusCall) {
+    spawn_dbus_thread(move || {
+        let connection = Connection::session().map_err(|e| e.to_string())?;
+
+        let mut arguments = StructureBuilder::new();
+
+        for argument in &call.arguments {
+            arguments = match argument {
+                DBusArgument::String(text) => arguments.add_field(text.clone()),
+                DBusArgument::Boolean(value) => arguments.add_field(*value),
+                DBusArgument::Int32(value) => arguments.add_field(*value),
+                DBusArgument::UInt32(value) => arguments.add_field(*value),
+                DBusArgument::Int64(value) => arguments.add_field(*value),
+                DBusArgument::Double(value) => arguments.add_field(*value),
+                DBusArgument::ObjectPath(path) => arguments.append_field(Value::ObjectPath(
+                    ObjectPath::try_from(path.as_str())
+                        .map_err(|e| e.to_string())?
+                        .into_owned(),
+                )),
+            };
+        }
+
+        let interface = if call.interface.trim().is_empty() {
+            None
+        } else {
+            Some(call.interface.trim())
+        };
+
+        let result = if call.arguments.is_empty() {
+            connection.call_method(
+                Some(call.destination.trim()),
+                call.path.trim(),
+                interface,
+                call.method.trim(),
+                &(),
+            )
+        } else {
+            connection.call_method(
+                Some(call.destination.trim()),
+                call.path.trim(),
+                interface,
+                call.method.trim(),
+                &arguments.build(),
+            )
+        };
+
+        result.map_err(|e| e.to_string())?;
+
+        log_info!(
+            "D-Bus method called: {} {} {}",
+            call.destination,
+            call.path,
+            call.method
+        );
+
+        Ok(())
+    });
+}
+
+#[cfg(target_os = "linux")]
+pub fn control_media_player(
+    action: MediaAction,
+    player: String,
+    value: f64,
+    report_now_playing: bool,
+) {
+    spawn_dbus_thread(move || {
+        let connection = Connection::session().map_err(|e| e.to_string())?;
+
+        let player = find_media_player(&connection, &player)?;
+
+        let call_player = |method: &str| {
+            connection
+                .call_method(
+                    Some(player.as_str()),
+                    MPRIS_PATH,
+                    Some(MPRIS_PLAYER_INTERFACE),
+                    method,
+                    &(),
+                )
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        };
+
+        match action {
+            MediaAction::PlayPause => call_player("PlayPause")?,
+            MediaAction::Play => call_player("Play")?,
+            MediaAction::Pause => call_player("Pause")?,
+            MediaAction::Stop => call_player("Stop")?,
+            MediaAction::Next => call_player("Next")?,
+            MediaAction::Previous => call_player("Previous")?,
+            MediaAction::Seek(seconds) => {
+                // MPRIS offsets are in microseconds
+                connection
+                    .call_method(
+                        Some(player.as_str()),
+                        MPRIS_PATH,
+                        Some(MPRIS_PLAYER_INTERFACE),
+                        "Seek",
+                        &(seconds * 1_000_000),
+                    )
+                    .map_err(|e| e.to_string())?;
+            }
+            MediaAction::Volume(offset) => {
+                let volume = get_player_property(&connection, &player, "Volume")
+                    .and_then(|v| f64::try_from(v).map_err(|e| e.to_string()))?;
+
+                set_player_volume(&connection, &player, volume + offset as f64 / 100.0)?;
+            }
+            MediaAction::SetVolume => {
+                // The value is mapped between 0-99 in the device
+                set_player_volume(&connection, &player, value / 99.0)?;
+            }
+        }
+
+        log_info!("Media action `{}` was sent to `{}`", action, player);
+
+        if report_now_playing {
+            // Give the player a moment to update its metadata after changing the track
+            std::thread::sleep(std::time::Duration::from_millis(300));
+
+            if let Some(now_playing) = get_now_playing(&connection, &player) {
+                show_on_display(&now_playing);
+            }
+        }
+
+        Ok(())
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn spawn_dbus_thread<F>(callback: F)
+where
+    F: FnOnce() -> Result<(), String> + Send + 'static,
+{
+    let spawn_result = std::thread::Builder::new()
+        .name("D-Bus".to_string())
+        .spawn(move || {
+            if let Err(e) = callback() {
+                log_error!("D-Bus interaction failed: {}", e);
+            }
+        });
+
+    if let Err(e) = spawn_result {
+        log_error!("Failed to spawn `D-Bus` thread: {}", e);
+    }
+}
+
+/// Find a player by (part of) its name, or the most recent one if `name` is empty
+#[cfg(target_os = "linux")]
+fn find_media_player(connection: &Connection, name: &str) -> Result<String, String> {
+    let reply = connection
+        .call_method(
+            Some("org.freedesktop.DBus"),
+            "/org/freedesktop/DBus",
+            Some("org.freedesktop.DBus"),
+            "ListNames",
+            &(),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let players: Vec<String> = reply
+        .body()
+        .deserialize::<Vec<String>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|bus_name| bus_name.starts_with(MPRIS_PREFIX))
+        .collect();
+
+    if players.is_empty() {
+        return Err("No MPRIS media players are running!".to_string());
+    }
+
+    let name = name.trim().to_lowercase();
+
+    if !name.is_empty() {
+        return players
+            .into_iter()
+            .find(|player| player[MPRIS_PREFIX.len()..].to_lowercase().contains(&name))
+            .ok_or(format!("Could not find a media player named `{}`", name));
+    }
+
+    let last_player = LAST_PLAYER.get_or_init(|| Mutex::new(String::new()));
+
+    for player in &players {
+        let status = get_player_property(connection, player, "PlaybackStatus")
+            .and_then(|v| String::try_from(v).map_err(|e| e.to_string()));
+
+        if status.as_deref() == Ok("Playing") {
+            if let Ok(mut last_player) = last_player.lock() {
+                *last_player = player.clone();
+            }
+
+            return Ok(player.clone());
+        }
+    }
+
+    if let Ok(last_player) = last_player.lock() {
+        if players.contains(&last_player) {
+            return Ok(last_player.clone());
+        }
+    }
+
+    Ok(players[0].clone())
+}
+
+#[cfg(target_os = "linux")]
+fn get_player_property(
+    connection: &Connection,
+    player: &str,
+    property: &str,
+) -> Result<OwnedValue, String> {
+    let reply = connection
+        .call_method(
+            Some(player),
+            MPRIS_PATH,
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &(MPRIS_PLAYER_INTERFACE, property),
+        )
+        .map_err(|e| e.to_string())?;
+
+    reply
+        .body()
+        .deserialize::<OwnedValue>()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn set_player_volume(connection: &Connection, player: &str, volume: f64) -> Result<(), String> {
+    connection
+        .call_method(
+            Some(player),
+            MPRIS_PATH,
+            Some("org.freedesktop.DBus.Properties"),
+            "Set",
+            &(
+                MPRIS_PLAYER_INTERFACE,
+                "Volume",
+                Value::from(volume.clamp(0.0, 1.0)),
+            ),
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Returns "Artist - Title" of the current track
+#[cfg(target_os = "linux")]
+fn get_now_playing(connection: &Connection, player: &str) -> Option<String> {
+    let metadata = get_player_property(connection, player, "Metadata").ok()?;
+    let metadata = std::collections::HashMap::<String, OwnedValue>::try_from(metadata).ok()?;
+
+    let title = metadata
+        .get("xesam:title")
+        .and_then(|v| String::try_from(v.try_clone().ok()?).ok())
+        .unwrap_or_default();
+    let artists = metadata
+        .get("xesam:artist")
+        .and_then(|v| Vec::<String>::try_from(v.try_clone().ok()?).ok())
+        .unwrap_or_default();
+
+    if title.is_empty() {
+        return None;
+    }
+
+    if artists.is_empty() {
+        return Some(title);
+    }
+
+    Some(format!("{} - {}", artists.join(", "), title))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn call_method(call: DBusCall) {
+    crate::log_error!(
+        "Could not call `{}`, D-Bus is only available on Linux!",
+        call.method
+    );
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn control_media_player(
+    action: MediaAction,
+    _player: String,
+    _value: f64,
+    _report_now_playing: bool,
+) {
+    crate::log_error!(
+        "Could not send media action `{}`, MPRIS is only available on Linux!",
+        action
+    );
+}
@@ -0,0 +1,427 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+    config::{FocusProfileSettings, CONFIG},
+    constants::FOCUS_POLL_INTERVAL,
+    log_info, log_warn,
+    service::{profile::switch_profile, shutdown::is_shutting_down},
+};
+
+// Automatic switching can be paused from the tray menu
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FocusedWindow {
+    pub class: String,
+    pub title: String,
+}
+
+/// Reports the focused window, implemented for each display server
+pub trait FocusBackend: Send {
+    fn get_focused_window(&mut self) -> Result<Option<FocusedWindow>, String>;
+}
+
+pub fn handle_focus_thread() {
+    let mut backend: Option<Box<dyn FocusBackend>> = None;
+
+    let mut last_window: Option<FocusedWindow> = None;
+    let mut last_error = String::new();
+    let mut expected_profile: Option<usize> = None; // Last profile that was seen or set here
+    let mut overridden = false; // Profile was switched manually while this window was focused
+
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(FOCUS_POLL_INTERVAL));
+
+        if is_shutting_down() {
+            return;
+        }
+
+        let (settings, current_profile) = {
+            let config = CONFIG
+                .get()
+                .expect("Could not retrieve CONFIG data!")
+                .lock()
+                .unwrap();
+
+            (
+                config.settings.focus_profiles.clone(),
+                config.settings.current_profile,
+            )
+        };
+
+        if !settings.enabled || PAUSED.load(Ordering::Relaxed) {
+            expected_profile = None;
+
+            continue;
+        }
+
+        if backend.is_none() {
+            backend = match create_backend() {
+                Some(b) => Some(b),
+                None => {
+                    log_warn!(
+                        "Automatic profile switching isn't supported on this system, \
+                        the focused window can't be detected!"
+                    );
+
+                    return;
+                }
+            };
+        }
+
+        let window = match backend.as_mut().map(|b| b.get_focused_window()) {
+            Some(Ok(Some(w))) => w,
+            Some(Ok(None)) | None => continue,
+            Some(Err(e)) => {
+                // Only log new errors, this runs a few times per second
+                if e != last_error {
+                    log_warn!("Could not get the focused window: {}", e);
+
+                    last_error = e;
+                }
+
+                continue;
+            }
+        };
+
+        if last_window.as_ref() != Some(&window) {
+            overridden = false;
+        } else if expected_profile.is_some_and(|p| p != current_profile) {
+            log_info!(
+                "Profile was switched manually, automatic switching is paused until focus changes."
+            );
+
+            overridden = true;
+        }
+
+        last_window = Some(window.clone());
+        expected_profile = Some(current_profile);
+
+        if overridden {
+            continue;
+        }
+
+        if let Some(profile_index) = find_profile_for_window(&settings, &window) {
+            if profile_index != current_profile && switch_profile(profile_index) {
+                expected_profile = Some(profile_index);
+            }
+        }
+    }
+}
+
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+
+    log_info!(
+        "Automatic profile switching is {}.",
+        if paused { "paused" } else { "resumed" }
+    );
+}
+
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+fn find_profile_for_window(
+    settings: &FocusProfileSettings,
+    window: &FocusedWindow,
+) -> Option<usize> {
+    let profile = find_rule_profile(settings, window);
+
+    if profile.trim().is_empty() {
+        return None;
+    }
+
+    let config = CONFIG
+        .get()
+        .expect("Could not retrieve CONFIG data!")
+        .lock()
+        .unwrap();
+
+    let profile_index = config.find_profile(profile);
+
+    if profile_index.is_none() {
+        log_warn!(
+            "Automatic profile switching: profile `{}` doesn't exist!",
+            profile
+        );
+    }
+
+    profile_index
+}
+
+/// Profile name (or index) of the first matching rule, `fallback` if none matches
+fn find_rule_profile<'a>(settings: &'a FocusProfileSettings, window: &FocusedWindow) -> &'a str {
+    let matches = |pattern: &str, text: &str| {
+        pattern.trim().is_empty() || text.to_lowercase().contains(&pattern.trim().to_lowercase())
+    };
+
+    settings
+        .rules
+        .iter()
+        .find(|rule| {
+            // Rules without any pattern would match every window
+            !(rule.class.trim().is_empty() && rule.title.trim().is_empty())
+                && matches(&rule.class, &window.class)
+                && matches(&rule.title, &window.title)
+        })
+        .map(|rule| rule.profile.as_str())
+        .unwrap_or(settings.fallback.as_str())
+}
+
+#[cfg(target_os = "linux")]
+fn create_backend() -> Option<Box<dyn FocusBackend>> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+            return Some(Box::new(linux::HyprlandBackend));
+        }
+
+        if std::env::var_os("SWAYSOCK").is_some() {
+            return Some(Box::new(linux::SwayBackend));
+        }
+
+        // Other compositors don't expose the focused window, only `XWayland` windows are seen
+        log_warn!("This Wayland compositor isn't supported, only XWayland windows are detected.");
+    }
+
+    linux::X11Backend::new()
+        .map(|backend| Box::new(backend) as Box<dyn FocusBackend>)
+        .ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn create_backend() -> Option<Box<dyn FocusBackend>> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::process::Command;
+
+    use x11rb::{
+        connection::Connection,
+        protocol::xproto::{Atom, AtomEnum, ConnectionExt, Window},
+        rust_connection::RustConnection,
+    };
+
+    use super::{FocusBackend, FocusedWindow};
+
+    /// Reads `_NET_ACTIVE_WINDOW` of the root window
+    pub struct X11Backend {
+        connection: RustConnection,
+        root: Window,
+        net_active_window: Atom,
+        net_wm_name: Atom,
+        utf8_string: Atom,
+    }
+
+    /// Uses `hyprctl activewindow -j`
+    pub struct HyprlandBackend;
+
+    /// Uses `swaymsg -t get_tree`
+    pub struct SwayBackend;
+
+    impl X11Backend {
+        pub fn new() -> Result<Self, String> {
+            let (connection, screen) = x11rb::connect(None).map_err(|e| e.to_string())?;
+            let root = connection.setup().roots[screen].root;
+
+            let intern_atom = |name: &[u8]| -> Result<Atom, String> {
+                Ok(connection
+                    .intern_atom(false, name)
+                    .map_err(|e| e.to_string())?
+                    .reply()
+                    .map_err(|e| e.to_string())?
+                    .atom)
+            };
+
+            let net_active_window = intern_atom(b"_NET_ACTIVE_WINDOW")?;
+            let net_wm_name = intern_atom(b"_NET_WM_NAME")?;
+            let utf8_string = intern_atom(b"UTF8_STRING")?;
+
+            Ok(Self {
+                connection,
+                root,
+                net_active_window,
+                net_wm_name,
+                utf8_string,
+            })
+        }
+
+        fn get_property(
+            &self,
+            window: Window,
+            property: Atom,
+            kind: Atom,
+        ) -> Result<Vec<u8>, String> {
+            Ok(self
+                .connection
+                .get_property(false, window, property, kind, 0, u32::MAX / 4)
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map_err(|e| e.to_string())?
+                .value)
+        }
+    }
+
+    impl FocusBackend for X11Backend {
+        fn get_focused_window(&mut self) -> Result<Option<FocusedWindow>, String> {
+            let active_window = self
+                .connection
+                .get_property(
+                    false,
+                    self.root,
+                    self.net_active_window,
+                    AtomEnum::WINDOW,
+                    0,
+                    1,
+                )
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map_err(|e| e.to_string())?
+                .value32()
+                .and_then(|mut value| value.next());
+
+            let window = match active_window {
+                Some(w) if w != 0 => w,
+                _ => return Ok(None),
+            };
+
+            // `WM_CLASS` is `instance\0class\0`
+            let class =
+                self.get_property(window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())?;
+            let class = String::from_utf8_lossy(&class)
+                .split('\0')
+                .rfind(|part| !part.is_empty())
+                .unwrap_or_default()
+                .to_string();
+
+            let mut title = self.get_property(window, self.net_wm_name, self.utf8_string)?;
+
+            if title.is_empty() {
+                title =
+                    self.get_property(window, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into())?;
+            }
+
+            Ok(Some(FocusedWindow {
+                class,
+                title: String::from_utf8_lossy(&title).to_string(),
+            }))
+        }
+    }
+
+    impl FocusBackend for HyprlandBackend {
+        fn get_focused_window(&mut self) -> Result<Option<FocusedWindow>, String> {
+            let window = run_json_command("hyprctl", &["activewindow", "-j"])?;
+
+            Ok(Some(FocusedWindow {
+                class: window["class"].as_str().unwrap_or_default().to_string(),
+                title: window["title"].as_str().unwrap_or_default().to_string(),
+            }))
+        }
+    }
+
+    impl FocusBackend for SwayBackend {
+        fn get_focused_window(&mut self) -> Result<Option<FocusedWindow>, String> {
+            let tree = run_json_command("swaymsg", &["-t", "get_tree"])?;
+
+            Ok(find_sway_focused_node(&tree).map(|node| FocusedWindow {
+                // Native Wayland windows have `app_id`, XWayland ones have `window_properties`
+                class: node["app_id"]
+                    .as_str()
+                    .or(node["window_properties"]["class"].as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                title: node["name"].as_str().unwrap_or_default().to_string(),
+            }))
+        }
+    }
+
+    fn find_sway_focused_node(node: &serde_json::Value) -> Option<&serde_json::Value> {
+        if node["focused"].as_bool() == Some(true) {
+            return Some(node);
+        }
+
+        ["nodes", "floating_nodes"]
+            .iter()
+            .filter_map(|key| node[key].as_array())
+            .flatten()
+            .find_map(find_sway_focused_node)
+    }
+
+    fn run_json_command(program: &str, args: &[&str]) -> Result<serde_json::Value, String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Could not run `{}`: {}", program, e))?;
+
+        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FocusProfileRule;
+
+    fn rule(profile: &str, class: &str, title: &str) -> FocusProfileRule {
+        FocusProfileRule {
+            profile: profile.to_string(),
+            class: class.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    fn window(class: &str, title: &str) -> FocusedWindow {
+        FocusedWindow {
+            class: class.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    fn settings() -> FocusProfileSettings {
+        FocusProfileSettings {
+            enabled: true,
+            fallback: "Default".to_string(),
+            rules: vec![
+                rule("", "", ""),
+                rule("Meetings", "firefox", "Meet"),
+                rule("Browsing", " Firefox ", ""),
+                rule("Editing", "", "- Visual Studio Code"),
+            ],
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let settings = settings();
+
+        assert_eq!(
+            find_rule_profile(&settings, &window("firefox", "Meet - abc-defg-hij")),
+            "Meetings"
+        );
+        assert_eq!(
+            find_rule_profile(&settings, &window("Firefox", "News")),
+            "Browsing"
+        );
+        assert_eq!(
+            find_rule_profile(&settings, &window("code", "main.rs - Visual Studio Code")),
+            "Editing"
+        );
+    }
+
+    #[test]
+    fn falls_back_without_a_match() {
+        let mut settings = settings();
+
+        // The rule without patterns is skipped instead of matching everything
+        assert_eq!(
+            find_rule_profile(&settings, &window("kitty", "~")),
+            "Default"
+        );
+
+        settings.fallback = String::new();
+
+        assert_eq!(find_rule_profile(&settings, &window("kitty", "~")), "");
+    }
+}
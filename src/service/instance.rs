@@ -0,0 +1,234 @@
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::{Read, Seek, Write},
+    path::Path,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::{
+    constants::{INSTANCE_LOCK_FILE_NAME, INSTANCE_LOCK_TIMEOUT, INSTANCE_RESTART_ENV_VAR},
+    log_error, log_info, log_warn,
+    tcp::{self, EndpointOwner},
+};
+
+// Kept open until the app exits, the OS releases the lock even if the app crashes
+static INSTANCE_LOCK: OnceLock<File> = OnceLock::new();
+
+/// Make sure only one `Service` runs for this user, returns `false` if it shouldn't start
+pub fn init() -> bool {
+    match acquire_lock() {
+        Ok(_) => (),
+        Err(pid) => {
+            handle_second_instance(pid);
+
+            return false;
+        }
+    }
+
+    // Another program (or an older `Service` without the lock file) may still use the endpoint
+    let endpoint = tcp::get_server_endpoint();
+
+    match tcp::identify_endpoint_owner(&endpoint) {
+        EndpointOwner::Nobody => true,
+        EndpointOwner::PadPad(version) => {
+            log_error!(
+                "PadPad `Service` v{} is already running on `{}` without the instance lock, close it first!",
+                version,
+                endpoint
+            );
+
+            false
+        }
+        EndpointOwner::OtherProgram => {
+            log_error!(
+                "`{}` is used by another program! Close it or change `[settings.ipc]` in the config file.",
+                endpoint
+            );
+
+            false
+        }
+    }
+}
+
+/// Lock the lock file and write our PID in it, returns the PID of the owner if it's locked
+fn acquire_lock() -> Result<(), Option<u32>> {
+    let path = tcp::get_ipc_file_path(INSTANCE_LOCK_FILE_NAME);
+
+    // A restarting `Service` waits for the old one to exit
+    let timeout = match std::env::var_os(INSTANCE_RESTART_ENV_VAR) {
+        Some(_) => Duration::from_millis(INSTANCE_LOCK_TIMEOUT),
+        None => Duration::ZERO,
+    };
+
+    // Commands that interactions start shouldn't inherit it
+    std::env::remove_var(INSTANCE_RESTART_ENV_VAR);
+
+    if let Some(file) = lock_file(&path, timeout)? {
+        INSTANCE_LOCK.set(file).ok();
+    }
+
+    Ok(())
+}
+
+/// Returns the locked file, `None` if locking isn't possible and the check should be skipped,
+/// or the PID of the owner if it's still locked after `timeout`
+fn lock_file(path: &Path, timeout: Duration) -> Result<Option<File>, Option<u32>> {
+    let mut file = match OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+    {
+        Ok(f) => f,
+        Err(e) => {
+            log_warn!(
+                "Could not open the lock file `{}`: {}, skipping the instance check!",
+                path.display(),
+                e
+            );
+
+            return Ok(None);
+        }
+    };
+
+    let started_at = Instant::now();
+
+    loop {
+        match file.try_lock() {
+            Ok(_) => break,
+            Err(TryLockError::WouldBlock) if started_at.elapsed() < timeout => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(TryLockError::WouldBlock) => {
+                let mut pid = String::new();
+
+                file.read_to_string(&mut pid).ok();
+
+                return Err(pid.trim().parse().ok());
+            }
+            Err(TryLockError::Error(e)) => {
+                log_warn!(
+                    "Could not lock `{}`: {}, skipping the instance check!",
+                    path.display(),
+                    e
+                );
+
+                return Ok(None);
+            }
+        }
+    }
+
+    // Only informational, the lock itself is what matters
+    file.set_len(0).ok();
+    file.rewind().ok();
+    write!(file, "{}", std::process::id()).ok();
+    file.flush().ok();
+
+    Ok(Some(file))
+}
+
+/// Ask the running `Service` to open its `Dashboard` (default) or restart with `--restart`
+fn handle_second_instance(pid: Option<u32>) {
+    let owner = match pid {
+        Some(pid) => format!("PID {}", pid),
+        None => "unknown PID".to_string(),
+    };
+
+    let endpoint = tcp::discover_endpoint();
+
+    if !matches!(
+        tcp::identify_endpoint_owner(&endpoint),
+        EndpointOwner::PadPad(_)
+    ) {
+        log_error!(
+            "Another instance of the app is already running ({}), but it isn't answering on `{}`!",
+            owner,
+            endpoint
+        );
+
+        return;
+    }
+
+    let is_restart = std::env::args().any(|arg| arg == "--restart");
+
+    log_info!(
+        "Another instance of the app is already running ({}), {}...",
+        owner,
+        if is_restart {
+            "restarting it"
+        } else {
+            "opening its dashboard (use `--restart` to restart it instead)"
+        }
+    );
+
+    let method = if is_restart {
+        "restart"
+    } else {
+        "open_dashboard"
+    };
+
+    if let Err(e) = tcp::client_to_server_message(method, Value::Null) {
+        log_error!("Could not reach the running instance: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock_file_path(name: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("padpad-{}-{}.lock", name, std::process::id()));
+
+        std::fs::remove_file(&path).ok();
+
+        path
+    }
+
+    #[test]
+    fn reports_the_lock_owner() {
+        let path = lock_file_path("owner");
+
+        let file = lock_file(&path, Duration::ZERO).unwrap();
+
+        assert!(file.is_some());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            std::process::id().to_string()
+        );
+        assert_eq!(
+            lock_file(&path, Duration::ZERO).unwrap_err(),
+            Some(std::process::id())
+        );
+
+        // The lock is released with the file
+        drop(file);
+
+        assert!(lock_file(&path, Duration::ZERO).unwrap().is_some());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn waits_for_the_lock() {
+        let path = lock_file_path("wait");
+
+        let file = lock_file(&path, Duration::ZERO).unwrap();
+
+        let owner = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+
+            drop(file);
+        });
+
+        assert!(lock_file(&path, Duration::from_secs(5)).unwrap().is_some());
+
+        owner.join().unwrap();
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -3,39 +3,474 @@ use enigo::{
     Enigo, Keyboard, Settings,
 };
 use open;
-use serde::{Deserialize, Serialize};
-use std::process::Command;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    collections::HashMap,
+    io::Read,
+    panic::AssertUnwindSafe,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
 
 use crate::{
     config::{ComponentKind, Interaction, CONFIG},
+    constants::{APP_NAME, COMMAND_OUTPUT_MAX_LENGTH, COMMAND_POLL_INTERVAL},
     log_error, log_info,
-    service::serial::Serial,
-    tcp,
+    service::{
+        bus::{self, DeviceEvent, InteractionEvent, ServiceEvent},
+        clipboard, dbus, mqtt, plugin,
+        schedule::parse_time,
+        script,
+        serial::{set_led, show_on_display},
+        template::{self, TemplateContext},
+    },
     utility::EnigoKey,
 };
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum InteractionKind {
     None(), /* Can be used for interactions that are handled by the device, or no interactions */
-    Command(String /* command */, String /* shell */),
+    #[serde(deserialize_with = "deserialize_command")]
+    Command(
+        String, /* command */
+        String, /* shell */
+        CommandOptions,
+    ),
     Application(String /* full_path */),
     Website(String /* url */),
-    Shortcut(Vec<EnigoKey> /* keys */, String /* text */),
+    #[serde(deserialize_with = "deserialize_shortcut")]
+    Shortcut(
+        Vec<EnigoKey>, /* keys */
+        String,        /* text */
+        bool,          /* hold: keys are pressed until the button is released */
+    ),
     File(String /* full_path */),
+    KeyScript(Vec<KeyStep> /* steps */, u32 /* repeat */),
+    Http(HttpRequest),
+    Mqtt(
+        String, /* topic */
+        String, /* payload */
+        bool,   /* retain */
+    ),
+    DBus(DBusCall),
+    Media(
+        MediaAction,
+        String, /* player: name (e.g. `spotify`) or empty for the most recent one */
+        bool,   /* report_now_playing: show the current track on the device's display */
+    ),
+    Script(String /* script */),
+    Clipboard(ClipboardAction),
+    Conditional(
+        Condition,
+        Box<InteractionKind>, /* then: run when the condition is true */
+        Box<InteractionKind>, /* otherwise: run when the condition is false */
+    ),
+    Plugin(PluginCall),
+}
+
+/// An interaction that's provided by a plugin, see `service::plugin`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PluginCall {
+    pub plugin: String,                                     // Plugin's name
+    pub interaction: String, // Interaction's id that's declared by the plugin
+    pub config: serde_json::Map<String, serde_json::Value>, // Values of the declared fields
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandOptions {
+    pub use_shell: bool, // `false` runs the program directly, arguments are split like a shell would
+    pub working_directory: String,
+    pub environment: Vec<(String /* name */, String /* value */)>,
+    pub timeout: u64, // The command is killed after this duration (in ms), `0` means no timeout
+    pub kill_previous: bool, // Kill the last run of this component's command if it's still running
+    pub display_output: bool, // Show the last line of the output on the device's display
+}
+
+/// Protects a component from being triggered too often. e.g. mechanical bounce
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriggerOptions {
+    pub cooldown: u64, // Minimum interval between triggers (in ms), `0` means no cooldown
+    pub debounce: u64, // Events closer than this to the previous event are ignored (in ms)
+    pub ignore_while_running: bool, // Ignore triggers while the previous run hasn't finished
+    pub confirmation: Confirmation, // Only used by buttons
+    pub armed_led: u8, // LED that's lit while waiting for confirmation, `0` means none
+}
+
+/// Buttons that run risky interactions can require a confirmation first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Confirmation {
+    #[default]
+    None,
+    DoublePress(
+        u64, /* window: the second press must come within this duration (in ms) */
+    ),
+    LongPress(
+        u64, /* duration: the button must be held this long (in ms) */
+    ),
+    Prompt, // A dialog is shown on the desktop
+}
+
+/// Marks an interaction of the component as running until it's dropped
+pub struct RunGuard(String /* component_global_id */);
+
+/// Captured result of the last command that was run by a component
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandOutput {
+    pub command: String,
+    pub status: String,
+    pub output: String, // stdout and stderr
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClipboardAction {
+    Copy(String /* snippet */),
+    Paste(
+        String, /* snippet */
+        bool,   /* restore: the previous content is put back after pasting */
+    ),
+    History(bool /* paste: also paste the selected entry */), // Each use selects an older entry
+}
+
+/// Checked right before a `Conditional` interaction runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    ProcessRunning(String /* name: e.g. `firefox` or `firefox.exe` */),
+    FileExists(String /* full_path */),
+    CommandSucceeds(String /* command */, String /* shell */),
+    TimeBetween(
+        String, /* start: `HH:MM` */
+        String, /* end: `HH:MM`, ranges that end before they start continue past midnight */
+    ),
+    ComponentValue(
+        String, /* component_global_id: e.g. `Potentiometer:1` */
+        Comparison,
+        String, /* value */
+    ),
+    Toggle(String /* name */), // Flips on each use and is kept between restarts
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Greater,
+    Less,
+}
+
+/// A single step of a `KeyScript`, steps are run one after another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum KeyStep {
+    Chord(Vec<EnigoKey> /* keys */), // Keys are pressed together. e.g. Ctrl+K
+    Text(String /* text */),
+    Delay(u64 /* duration (in ms) */),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String /* name */, String /* value */)>,
+    pub body: String,
+    pub timeout: u64, // Waiting duration for the response (in ms)
+    pub response: HttpResponseAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HttpResponseAction {
+    Log,     // Only write the response status to the log
+    Display, // Also show the response status on the device's display
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DBusCall {
+    pub destination: String, // e.g. `org.freedesktop.Notifications`
+    pub path: String,        // e.g. `/org/freedesktop/Notifications`
+    pub interface: String,   // e.g. `org.freedesktop.Notifications`
+    pub method: String,      // e.g. `Notify`
+    pub arguments: Vec<DBusArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DBusArgument {
+    String(String),
+    Boolean(bool),
+    Int32(i32),
+    UInt32(u32),
+    Int64(i64),
+    Double(f64),
+    ObjectPath(String),
+}
+
+/// MPRIS player actions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MediaAction {
+    PlayPause,
+    Play,
+    Pause,
+    Stop,
+    Next,
+    Previous,
+    Seek(i64 /* offset (in seconds) */),
+    Volume(i8 /* offset (in percent) */),
+    SetVolume, // Uses the component's value. e.g. potentiometer 0-99
 }
 
 impl InteractionKind {
     pub fn equals_kind(&self, other: &Self) -> bool {
         match (self, other) {
             (InteractionKind::None(), InteractionKind::None()) => true,
-            (InteractionKind::Command(_, _), InteractionKind::Command(_, _)) => true,
+            (InteractionKind::Command(_, _, _), InteractionKind::Command(_, _, _)) => true,
             (InteractionKind::Application(_), InteractionKind::Application(_)) => true,
             (InteractionKind::Website(_), InteractionKind::Website(_)) => true,
-            (InteractionKind::Shortcut(_, _), InteractionKind::Shortcut(_, _)) => true,
+            (InteractionKind::Shortcut(_, _, _), InteractionKind::Shortcut(_, _, _)) => true,
             (InteractionKind::File(_), InteractionKind::File(_)) => true,
+            (InteractionKind::KeyScript(_, _), InteractionKind::KeyScript(_, _)) => true,
+            (InteractionKind::Http(_), InteractionKind::Http(_)) => true,
+            (InteractionKind::Mqtt(_, _, _), InteractionKind::Mqtt(_, _, _)) => true,
+            (InteractionKind::DBus(_), InteractionKind::DBus(_)) => true,
+            (InteractionKind::Media(_, _, _), InteractionKind::Media(_, _, _)) => true,
+            (InteractionKind::Script(_), InteractionKind::Script(_)) => true,
+            (InteractionKind::Clipboard(_), InteractionKind::Clipboard(_)) => true,
+            (InteractionKind::Conditional(_, _, _), InteractionKind::Conditional(_, _, _)) => true,
+            (InteractionKind::Plugin(_), InteractionKind::Plugin(_)) => true,
             _ => false,
         }
     }
+
+    /// Text fields that support templates. e.g. `{value}`, `{value|map:0,99,-10,10}`
+    pub fn templates(&self) -> Vec<&str> {
+        match self {
+            InteractionKind::None() => vec![],
+            InteractionKind::Command(command, _, options) => {
+                let mut templates = vec![command.as_str(), options.working_directory.as_str()];
+
+                templates.extend(options.environment.iter().map(|(_, value)| value.as_str()));
+
+                templates
+            }
+            InteractionKind::Application(app_full_path) => vec![app_full_path],
+            InteractionKind::Website(website_url) => vec![website_url],
+            InteractionKind::Shortcut(_, text, _) => vec![text],
+            InteractionKind::File(file_full_path) => vec![file_full_path],
+            InteractionKind::KeyScript(steps, _) => steps
+                .iter()
+                .filter_map(|step| match step {
+                    KeyStep::Text(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            InteractionKind::Http(request) => {
+                let mut templates = vec![request.url.as_str(), request.body.as_str()];
+
+                templates.extend(request.headers.iter().map(|(_, value)| value.as_str()));
+
+                templates
+            }
+            InteractionKind::Mqtt(topic, payload, _) => vec![topic, payload],
+            InteractionKind::DBus(call) => call
+                .arguments
+                .iter()
+                .filter_map(|argument| match argument {
+                    DBusArgument::String(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            InteractionKind::Media(_, _, _) => vec![],
+            InteractionKind::Script(_) => vec![],
+            InteractionKind::Clipboard(action) => match action {
+                ClipboardAction::Copy(snippet) | ClipboardAction::Paste(snippet, _) => {
+                    vec![snippet]
+                }
+                ClipboardAction::History(_) => vec![],
+            },
+            // Nested interactions have their own templates
+            InteractionKind::Conditional(condition, _, _) => match condition {
+                Condition::FileExists(path) => vec![path],
+                Condition::CommandSucceeds(command, _) => vec![command],
+                Condition::ComponentValue(_, _, value) => vec![value],
+                _ => vec![],
+            },
+            InteractionKind::Plugin(call) => call
+                .config
+                .values()
+                .filter_map(|value| value.as_str())
+                .collect(),
+        }
+    }
+
+    /// This interaction and the ones nested inside it. e.g. branches of a `Conditional`
+    pub fn kinds(&self) -> Vec<&InteractionKind> {
+        match self {
+            InteractionKind::Conditional(_, then, otherwise) => {
+                let mut kinds = vec![self];
+
+                kinds.extend(then.kinds());
+                kinds.extend(otherwise.kinds());
+
+                kinds
+            }
+            _ => vec![self],
+        }
+    }
+
+    /// Check templates and scripts for errors, so they're rejected before being saved
+    pub fn validate(&self) -> Result<(), String> {
+        self.templates()
+            .into_iter()
+            .try_for_each(template::validate)
+            .map_err(|e| format!("Invalid template {}", e))?;
+
+        match self {
+            InteractionKind::Script(script) => {
+                script::validate(script).map_err(|e| format!("Invalid script: {}", e))?;
+            }
+            InteractionKind::Conditional(condition, then, otherwise) => {
+                if let Condition::TimeBetween(start, end) = condition {
+                    parse_time(start).and(parse_time(end))?;
+                }
+
+                then.validate()?;
+                otherwise.validate()?;
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for CommandOptions {
+    fn default() -> Self {
+        Self {
+            use_shell: true,
+            working_directory: String::new(),
+            environment: vec![],
+            timeout: 0,
+            kill_previous: false,
+            display_output: false,
+        }
+    }
+}
+
+impl std::fmt::Display for Confirmation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Confirmation::DoublePress(_) => write!(f, "Double Press"),
+            Confirmation::LongPress(_) => write!(f, "Long Press"),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl RunGuard {
+    pub fn new(component_global_id: &str) -> Self {
+        if let Ok(mut running_interactions) = get_running_interactions().lock() {
+            *running_interactions
+                .entry(component_global_id.to_string())
+                .or_insert(0) += 1;
+        }
+
+        Self(component_global_id.to_string())
+    }
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        if let Ok(mut running_interactions) = get_running_interactions().lock() {
+            if let Some(count) = running_interactions.get_mut(&self.0) {
+                *count = count.saturating_sub(1);
+
+                if *count == 0 {
+                    running_interactions.remove(&self.0);
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for KeyStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeyStep::Chord(keys) => write!(
+                f,
+                "{}",
+                keys.iter()
+                    .map(|key| match key.0 {
+                        enigo::Key::Unicode(letter) => letter.to_uppercase().to_string(),
+                        _ => format!("{:?}", key.0),
+                    })
+                    .collect::<Vec<String>>()
+                    .join("+")
+            ),
+            KeyStep::Text(text) => write!(f, "\"{}\"", text),
+            KeyStep::Delay(duration) => write!(f, "{}ms", duration),
+        }
+    }
+}
+
+impl Default for HttpRequest {
+    fn default() -> Self {
+        Self {
+            method: "GET".to_string(),
+            url: String::new(),
+            headers: vec![],
+            body: String::new(),
+            timeout: 5000,
+            response: HttpResponseAction::Log,
+        }
+    }
+}
+
+impl std::fmt::Display for MediaAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MediaAction::PlayPause => write!(f, "Play/Pause"),
+            MediaAction::Seek(_) => write!(f, "Seek"),
+            MediaAction::Volume(_) => write!(f, "Volume"),
+            MediaAction::SetVolume => write!(f, "Set Volume"),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl std::fmt::Display for ClipboardAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ClipboardAction::Copy(_) => write!(f, "Copy Snippet"),
+            ClipboardAction::Paste(_, _) => write!(f, "Paste Snippet"),
+            ClipboardAction::History(_) => write!(f, "Clipboard History"),
+        }
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Condition::ProcessRunning(_) => write!(f, "Process Is Running"),
+            Condition::FileExists(_) => write!(f, "File Exists"),
+            Condition::CommandSucceeds(_, _) => write!(f, "Command Succeeds"),
+            Condition::TimeBetween(_, _) => write!(f, "Time Of Day"),
+            Condition::ComponentValue(_, _, _) => write!(f, "Component Value"),
+            Condition::Toggle(_) => write!(f, "Toggle"),
+        }
+    }
+}
+
+impl std::fmt::Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Comparison::Equal => write!(f, "="),
+            Comparison::NotEqual => write!(f, "≠"),
+            Comparison::Greater => write!(f, ">"),
+            Comparison::Less => write!(f, "<"),
+        }
+    }
 }
 
 impl std::fmt::Display for InteractionKind {
@@ -46,35 +481,355 @@ impl std::fmt::Display for InteractionKind {
     }
 }
 
-fn run_command(command: &str, unix_shell: &str) {
-    let cmd = command.trim();
+// Keys that are held down by `hold` shortcuts, until their button is released
+static HELD_KEYS: OnceLock<Mutex<HashMap<String /* component_global_id */, Vec<EnigoKey>>>> =
+    OnceLock::new();
 
-    if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(&["/C", cmd])
-            .spawn()
-            .expect("Failed to run command");
+// Last value that was received from each component, read by scripts
+static COMPONENT_VALUES: OnceLock<Mutex<HashMap<String /* component_global_id */, String>>> =
+    OnceLock::new();
+
+// Last value of each component that triggered an interaction, used by `{prev_value}`
+static LAST_VALUES: OnceLock<Mutex<HashMap<String /* component_global_id */, String>>> =
+    OnceLock::new();
+
+// Last event and last accepted trigger of each component, used by `TriggerOptions`
+static LAST_EVENTS: OnceLock<Mutex<HashMap<String /* component_global_id */, Instant>>> =
+    OnceLock::new();
+static LAST_TRIGGERS: OnceLock<Mutex<HashMap<String /* component_global_id */, Instant>>> =
+    OnceLock::new();
+
+// Last potentiometer value that was suppressed by `TriggerOptions`, applied at its deadline
+static DEFERRED_VALUES: OnceLock<
+    Mutex<HashMap<String /* component_global_id */, (u8 /* value */, Instant /* deadline */)>>,
+> = OnceLock::new();
+
+type ArmedInteraction = (
+    u64, /* arm_id */
+    InteractionKind,
+    bool, /* modkey */
+);
+
+// Interactions that are waiting for confirmation, they're cancelled if `arm_id` has changed
+static ARMED_INTERACTIONS: OnceLock<
+    Mutex<HashMap<String /* component_global_id */, ArmedInteraction>>,
+> = OnceLock::new();
+static NEXT_ARM_ID: AtomicU64 = AtomicU64::new(0);
+
+// Number of interactions of each component that run on their own thread and haven't finished
+static RUNNING_INTERACTIONS: OnceLock<Mutex<HashMap<String /* component_global_id */, usize>>> =
+    OnceLock::new();
+
+// Commands that haven't exited yet, so they can be killed when re-triggered or timed out
+static RUNNING_COMMANDS: OnceLock<
+    Mutex<HashMap<u64 /* run_id */, (String /* component_global_id */, Child)>>,
+> = OnceLock::new();
+static NEXT_COMMAND_RUN_ID: AtomicU64 = AtomicU64::new(0);
+
+// Captured output of the last command of each component, requested by the `Dashboard`
+static COMMAND_OUTPUTS: OnceLock<Mutex<HashMap<String /* component_global_id */, CommandOutput>>> =
+    OnceLock::new();
+
+// Older config files don't have the `options` field for commands
+fn deserialize_command<'de, D>(
+    deserializer: D,
+) -> Result<(String, String, CommandOptions), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Command {
+        Legacy(String, String),
+        Current(String, String, CommandOptions),
+    }
+
+    Ok(match Command::deserialize(deserializer)? {
+        Command::Legacy(command, shell) => (command, shell, CommandOptions::default()),
+        Command::Current(command, shell, options) => (command, shell, options),
+    })
+}
+
+// Older config files don't have the `hold` field for shortcuts
+fn deserialize_shortcut<'de, D>(deserializer: D) -> Result<(Vec<EnigoKey>, String, bool), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Shortcut {
+        Legacy(Vec<EnigoKey>, String),
+        Current(Vec<EnigoKey>, String, bool),
+    }
+
+    Ok(match Shortcut::deserialize(deserializer)? {
+        Shortcut::Legacy(keys, text) => (keys, text, false),
+        Shortcut::Current(keys, text, hold) => (keys, text, hold),
+    })
+}
+
+fn run_command(
+    component_global_id: String,
+    command: &str,
+    unix_shell: &str,
+    options: CommandOptions,
+) {
+    let cmd = command.trim().to_string();
+
+    let mut process = if !options.use_shell {
+        let arguments = match shlex::split(&cmd) {
+            Some(a) if !a.is_empty() => a,
+            _ => {
+                log_error!("Could not parse the arguments of the command `{}`", cmd);
+
+                return;
+            }
+        };
+
+        let mut process = Command::new(&arguments[0]);
+
+        process.args(&arguments[1..]);
+
+        process
     } else {
-        let shell = unix_shell.trim();
+        create_shell_process(&cmd, unix_shell)
+    };
+
+    if !options.working_directory.trim().is_empty() {
+        process.current_dir(options.working_directory.trim());
+    }
+
+    for (name, value) in &options.environment {
+        if !name.trim().is_empty() {
+            process.env(name.trim(), value);
+        }
+    }
+
+    process
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
 
-        Command::new(shell)
-            .arg("-c")
-            .arg(cmd)
-            .spawn()
-            .expect("Failed to run command");
+    if options.kill_previous {
+        kill_running_commands(&component_global_id);
     }
 
+    let mut child = match process.spawn() {
+        Ok(c) => c,
+        Err(e) => {
+            log_error!("Failed to run command `{}`: {}", cmd, e);
+
+            return;
+        }
+    };
+
     log_info!("Command executed: {}", cmd);
+
+    let output = Arc::new(Mutex::new(String::new()));
+    let readers = [
+        child
+            .stdout
+            .take()
+            .map(|pipe| read_command_pipe(pipe, output.clone())),
+        child
+            .stderr
+            .take()
+            .map(|pipe| read_command_pipe(pipe, output.clone())),
+    ];
+
+    let run_id = NEXT_COMMAND_RUN_ID.fetch_add(1, Ordering::Relaxed);
+
+    if let Ok(mut running_commands) = get_running_commands().lock() {
+        running_commands.insert(run_id, (component_global_id.clone(), child));
+    }
+
+    // Waiting happens on a separate thread, so long-running commands don't block serial
+    let spawn_result = std::thread::Builder::new()
+        .name("Command".to_string())
+        .spawn(move || {
+            let status = wait_for_command(run_id, options.timeout);
+
+            // Give the readers a moment to catch the last bits of the output
+            let deadline = std::time::Instant::now() + std::time::Duration::from_millis(100);
+
+            while readers.iter().flatten().any(|reader| !reader.is_finished())
+                && std::time::Instant::now() < deadline
+            {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+
+            let output = output.lock().map(|o| o.clone()).unwrap_or_default();
+
+            log_info!("Command `{}` finished: {}", cmd, status);
+
+            if options.display_output {
+                let last_line = output
+                    .lines()
+                    .rev()
+                    .find(|line| !line.trim().is_empty())
+                    .unwrap_or(&status);
+
+                show_on_display(last_line.trim());
+            }
+
+            let command_output = CommandOutput {
+                command: cmd,
+                status,
+                output,
+            };
+
+            if let Ok(mut command_outputs) = get_command_outputs().lock() {
+                command_outputs.insert(component_global_id.clone(), command_output.clone());
+            }
+
+            bus::publish(ServiceEvent::Interaction(
+                InteractionEvent::CommandFinished(component_global_id, command_output),
+            ));
+        });
+
+    if let Err(e) = spawn_result {
+        log_error!("Failed to spawn `Command` thread: {}", e);
+
+        // Nothing would time it out or reap it otherwise
+        let child = get_running_commands()
+            .lock()
+            .ok()
+            .and_then(|mut running_commands| running_commands.remove(&run_id));
+
+        if let Some((_, mut child)) = child {
+            child.kill().ok();
+            child.wait().ok();
+        }
+    }
+}
+
+fn create_shell_process(command: &str, unix_shell: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut process = Command::new("cmd");
+
+        process.args(["/C", command]);
+
+        return process;
+    }
+
+    let shell = match unix_shell.trim() {
+        "" => "sh",
+        shell => shell,
+    };
+
+    let mut process = Command::new(shell);
+
+    process.arg("-c").arg(command);
+
+    process
+}
+
+/// Returns the exit status of the command, once it exits, times out or gets killed
+fn wait_for_command(run_id: u64, timeout: u64) -> String {
+    let started_at = std::time::Instant::now();
+
+    loop {
+        if let Ok(mut running_commands) = get_running_commands().lock() {
+            let child = match running_commands.get_mut(&run_id) {
+                Some((_, child)) => child,
+                None => return "killed".to_string(),
+            };
+
+            let status = match child.try_wait() {
+                Ok(Some(status)) => Some(status.to_string()),
+                Ok(None) if timeout > 0 && started_at.elapsed().as_millis() >= timeout as u128 => {
+                    child.kill().ok();
+                    child.wait().ok();
+
+                    Some(format!("timed out after {}ms", timeout))
+                }
+                Ok(None) => None,
+                Err(e) => Some(e.to_string()),
+            };
+
+            if let Some(status) = status {
+                running_commands.remove(&run_id);
+
+                return status;
+            }
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(COMMAND_POLL_INTERVAL));
+    }
+}
+
+fn read_command_pipe(
+    mut pipe: impl Read + Send + 'static,
+    output: Arc<Mutex<String>>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut buffer = [0; 1024];
+
+        while let Ok(bytes_read) = pipe.read(&mut buffer) {
+            if bytes_read == 0 {
+                break;
+            }
+
+            if let Ok(mut output) = output.lock() {
+                output.push_str(&String::from_utf8_lossy(&buffer[..bytes_read]));
+
+                // Only keep the end of the output
+                if output.len() > COMMAND_OUTPUT_MAX_LENGTH {
+                    let mut start = output.len() - COMMAND_OUTPUT_MAX_LENGTH;
+
+                    while !output.is_char_boundary(start) {
+                        start += 1;
+                    }
+
+                    output.drain(..start);
+                }
+            }
+        }
+    })
+}
+
+fn kill_running_commands(component_global_id: &str) {
+    if let Ok(mut running_commands) = get_running_commands().lock() {
+        running_commands.retain(|_, (id, child)| {
+            if id != component_global_id {
+                return true;
+            }
+
+            if let Err(e) = child.kill() {
+                log_error!("Failed to kill the previous command: {}", e);
+            }
+
+            child.wait().ok();
+
+            false
+        });
+    }
+}
+
+/// Returns the captured output of the last command that was run by the component
+pub fn get_command_output(component_global_id: &str) -> Option<CommandOutput> {
+    get_command_outputs()
+        .lock()
+        .ok()?
+        .get(component_global_id)
+        .cloned()
+}
+
+fn get_running_commands() -> &'static Mutex<HashMap<u64, (String, Child)>> {
+    RUNNING_COMMANDS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_command_outputs() -> &'static Mutex<HashMap<String, CommandOutput>> {
+    COMMAND_OUTPUTS.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
 fn open_application(app_full_path: &str) {
     let app_path = app_full_path.trim();
 
-    Command::new(app_path)
-        .spawn()
-        .expect("Failed to open application");
-
-    log_info!("Application opened: {}", app_path);
+    match Command::new(app_path).spawn() {
+        Ok(_) => log_info!("Application opened: {}", app_path),
+        Err(e) => log_error!("Failed to open application `{}`: {}", app_path, e),
+    }
 }
 
 fn open_website(website_url: &str) {
@@ -86,13 +841,14 @@ fn open_website(website_url: &str) {
         format!("https://{}", url)
     };
 
-    open::that_detached(&full_url).expect("Failed to open website");
-
-    log_info!("Website opened: {}", full_url);
+    match open::that_detached(&full_url) {
+        Ok(_) => log_info!("Website opened: {}", full_url),
+        Err(e) => log_error!("Failed to open website `{}`: {}", full_url, e),
+    }
 }
 
 // If `text` parameter is NOT empty, `keys` will be ignored
-fn simulate_shortcut(keys: &Vec<EnigoKey> /* Vec<enigo::Key> */, text: &str) {
+pub fn simulate_shortcut(keys: &[EnigoKey] /* [enigo::Key] */, text: &str) {
     let mut enigo = Enigo::new(&Settings::default()).unwrap();
 
     if text.is_empty() {
@@ -124,56 +880,929 @@ fn simulate_shortcut(keys: &Vec<EnigoKey> /* Vec<enigo::Key> */, text: &str) {
     }
 }
 
-fn open_file(file_full_path: &str) {
-    let file_path = file_full_path.trim();
+fn press_held_keys(component_global_id: String, keys: &[EnigoKey]) {
+    // Release previous keys first, in case the release message was lost
+    release_held_keys(&component_global_id);
+
+    let mut enigo = Enigo::new(&Settings::default()).unwrap();
 
-    open::that_detached(&file_path).expect("Failed to open file");
+    for key in keys.iter() {
+        if let Err(e) = enigo.key(key.0, Press) {
+            log_error!(
+                "Shortcut simulation failed while pressing key {:?}: {:?}",
+                key,
+                e
+            );
+        }
+    }
 
-    log_info!("File opened: {}", file_path);
+    if let Ok(mut held_keys) = get_held_keys().lock() {
+        held_keys.insert(component_global_id, keys.to_vec());
+    }
 }
 
-fn do_interaction(kind: &InteractionKind, value: impl ToString) {
-    let parse_value = |text: &str| text.replace("{value}", &value.to_string());
+pub fn release_held_keys(component_global_id: &str) {
+    let keys = match get_held_keys().lock() {
+        Ok(mut held_keys) => held_keys.remove(component_global_id),
+        Err(_) => None,
+    };
 
-    match kind {
-        InteractionKind::None() => (),
-        InteractionKind::Command(command, unix_shell) => {
-            run_command(&parse_value(command), &unix_shell)
-        }
-        InteractionKind::Application(app_full_path) => {
-            open_application(&parse_value(app_full_path))
-        }
-        InteractionKind::Website(website_url) => open_website(&parse_value(website_url)),
-        InteractionKind::Shortcut(keys, text) => simulate_shortcut(keys, &parse_value(text)),
-        InteractionKind::File(file_full_path) => open_file(&parse_value(file_full_path)),
+    if let Some(keys) = keys {
+        release_keys(&keys);
     }
 }
 
-fn update_server_data_component(component_global_id: String, value: String) {
-    if let Ok(mut data) = tcp::get_server_data().lock() {
-        let mut server_data = data.clone();
+/// Release every held key, e.g. when the device was disconnected mid-hold
+pub fn release_all_held_keys() {
+    let all_keys: Vec<Vec<EnigoKey>> = match get_held_keys().lock() {
+        Ok(mut held_keys) => held_keys.drain().map(|(_, keys)| keys).collect(),
+        Err(_) => vec![],
+    };
 
-        server_data.last_updated_component = (component_global_id, value);
+    if !all_keys.is_empty() {
+        log_info!("Releasing all held keys...");
+    }
 
-        *data = server_data;
+    for keys in all_keys {
+        release_keys(&keys);
     }
 }
 
-fn get_component_interactions(component_global_id: String) -> Option<Interaction> {
-    let config = CONFIG
-        .get()
-        .expect("Could not retrieve CONFIG data!")
-        .lock()
-        .unwrap();
+fn release_keys(keys: &[EnigoKey]) {
+    let mut enigo = Enigo::new(&Settings::default()).unwrap();
 
-    let current_profile = &config.profiles[config.settings.current_profile];
+    for key in keys.iter().rev() {
+        if let Err(e) = enigo.key(key.0, Release) {
+            log_error!(
+                "Shortcut simulation failed while releasing key {:?}: {:?}",
+                key,
+                e
+            );
+        }
+    }
+}
 
-    let interactions = current_profile
-        .interactions
-        .get(&component_global_id)
-        .cloned();
+fn get_held_keys() -> &'static Mutex<HashMap<String, Vec<EnigoKey>>> {
+    HELD_KEYS.get_or_init(|| Mutex::new(HashMap::new()))
+}
 
-    if interactions.is_none() {
+// Scripts are run on a separate thread, so delays don't block serial
+fn run_key_script(run_guard: RunGuard, steps: Vec<KeyStep>, repeat: u32) {
+    let spawn_result = std::thread::Builder::new()
+        .name("Key Script".to_string())
+        .spawn(move || {
+            let _run_guard = run_guard;
+
+            for _ in 0..repeat.max(1) {
+                for step in &steps {
+                    match step {
+                        KeyStep::Chord(keys) => simulate_shortcut(keys, ""),
+                        KeyStep::Text(text) => simulate_shortcut(&[], text),
+                        KeyStep::Delay(duration) => {
+                            std::thread::sleep(std::time::Duration::from_millis(*duration))
+                        }
+                    }
+                }
+            }
+
+            log_info!(
+                "Key script executed: {}",
+                steps
+                    .iter()
+                    .map(|step| step.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+        });
+
+    if let Err(e) = spawn_result {
+        log_error!("Failed to spawn `Key Script` thread: {}", e);
+    }
+}
+
+fn open_file(file_full_path: &str) {
+    let file_path = file_full_path.trim();
+
+    match open::that_detached(file_path) {
+        Ok(_) => log_info!("File opened: {}", file_path),
+        Err(e) => log_error!("Failed to open file `{}`: {}", file_path, e),
+    }
+}
+
+// The request is sent on a separate thread, so waiting for the response doesn't block serial
+fn send_http_request(run_guard: RunGuard, request: HttpRequest) {
+    let spawn_result = std::thread::Builder::new()
+        .name("HTTP Request".to_string())
+        .spawn(move || {
+            let _run_guard = run_guard;
+
+            let status = perform_http_request(&request);
+
+            if request.response == HttpResponseAction::Display {
+                show_on_display(&status);
+            }
+        });
+
+    if let Err(e) = spawn_result {
+        log_error!("Failed to spawn `HTTP Request` thread: {}", e);
+    }
+}
+
+fn build_http_request(request: &HttpRequest) -> ureq::Request {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_millis(request.timeout))
+        .build();
+
+    let mut http_request = agent.request(&request.method.trim().to_uppercase(), request.url.trim());
+
+    for (name, value) in &request.headers {
+        if name.trim().is_empty() {
+            continue;
+        }
+
+        http_request = http_request.set(name.trim(), value);
+    }
+
+    http_request
+}
+
+/// Returns the status that's reported to the user, e.g. `200 OK` or `Request failed`
+fn perform_http_request(request: &HttpRequest) -> String {
+    let http_request = build_http_request(request);
+
+    let method = http_request.method().to_string();
+    let url = http_request.url().to_string();
+
+    let result = if request.body.is_empty() {
+        http_request.call()
+    } else {
+        http_request.send_string(&request.body)
+    };
+
+    match result {
+        Ok(response) => {
+            let status = format!("{} {}", response.status(), response.status_text());
+
+            log_info!(
+                "HTTP request `{} {}` responded with `{}`",
+                method,
+                url,
+                status
+            );
+
+            status
+        }
+        Err(ureq::Error::Status(code, response)) => {
+            let status = format!("{} {}", code, response.status_text());
+
+            log_error!("HTTP request `{} {}` failed with `{}`", method, url, status);
+
+            status
+        }
+        Err(ureq::Error::Transport(e)) => {
+            log_error!("HTTP request `{} {}` failed: {}", method, url, e);
+
+            "Request failed".to_string()
+        }
+    }
+}
+
+// Conditions are checked on a separate thread, since commands and processes can take a while
+fn run_conditional(
+    component_global_id: String,
+    condition: Condition,
+    branches: (
+        InteractionKind, /* then */
+        InteractionKind, /* otherwise */
+    ),
+    context: TemplateContext,
+    modkey: bool,
+) {
+    let run_guard = RunGuard::new(&component_global_id);
+
+    let spawn_result = std::thread::Builder::new()
+        .name("Conditional".to_string())
+        .spawn(move || {
+            let _run_guard = run_guard;
+
+            let is_met = match check_condition(&condition) {
+                Ok(m) => m,
+                Err(e) => {
+                    log_error!("Could not check the condition `{}`: {}", condition, e);
+
+                    return;
+                }
+            };
+
+            log_info!(
+                "Condition `{}` of `{}` is {}.",
+                condition,
+                component_global_id,
+                if is_met { "met" } else { "not met" }
+            );
+
+            let interaction = if is_met { &branches.0 } else { &branches.1 };
+
+            run_interaction(&component_global_id, interaction, &context, modkey);
+        });
+
+    if let Err(e) = spawn_result {
+        log_error!("Failed to spawn `Conditional` thread: {}", e);
+    }
+}
+
+fn check_condition(condition: &Condition) -> Result<bool, String> {
+    match condition {
+        Condition::ProcessRunning(name) => is_process_running(name.trim()),
+        Condition::FileExists(path) => Ok(std::path::Path::new(path.trim()).exists()),
+        Condition::CommandSucceeds(command, unix_shell) => {
+            create_shell_process(command, unix_shell)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|status| status.success())
+                .map_err(|e| format!("Failed to run command `{}`: {}", command, e))
+        }
+        Condition::TimeBetween(start, end) => {
+            let (start, end) = (parse_time(start)?, parse_time(end)?);
+            let now = chrono::Local::now().time();
+
+            if start <= end {
+                Ok(now >= start && now < end)
+            } else {
+                Ok(now >= start || now < end)
+            }
+        }
+        Condition::ComponentValue(component_global_id, comparison, expected) => {
+            // Components that haven't sent anything yet don't meet any comparison
+            let value = match get_component_value(component_global_id.trim()) {
+                Some(v) => v,
+                None => return Ok(false),
+            };
+
+            let ordering = match (value.parse::<f64>(), expected.trim().parse::<f64>()) {
+                (Ok(value), Ok(expected)) => value.partial_cmp(&expected),
+                _ => Some(value.as_str().cmp(expected.trim())),
+            };
+
+            Ok(match comparison {
+                Comparison::Equal => ordering == Some(std::cmp::Ordering::Equal),
+                Comparison::NotEqual => ordering != Some(std::cmp::Ordering::Equal),
+                Comparison::Greater => ordering == Some(std::cmp::Ordering::Greater),
+                Comparison::Less => ordering == Some(std::cmp::Ordering::Less),
+            })
+        }
+        Condition::Toggle(name) => Ok(script::toggle_variable(name.trim())),
+    }
+}
+
+fn is_process_running(name: &str) -> Result<bool, String> {
+    if cfg!(target_os = "windows") {
+        let output = Command::new("tasklist")
+            .args(["/FI", &format!("IMAGENAME eq {}", name), "/NH"])
+            .output()
+            .map_err(|e| format!("Could not run `tasklist`: {}", e))?;
+
+        return Ok(String::from_utf8_lossy(&output.stdout)
+            .to_lowercase()
+            .contains(&name.to_lowercase()));
+    }
+
+    Command::new("pgrep")
+        .args(["-x", name])
+        .stdout(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .map_err(|e| format!("Could not run `pgrep`: {}", e))
+}
+
+fn do_interaction(
+    component_global_id: &str,
+    kind: &InteractionKind,
+    value: impl ToString,
+    modkey: bool,
+) {
+    let context = get_template_context(component_global_id, value.to_string());
+
+    run_interaction(component_global_id, kind, &context, modkey);
+}
+
+fn run_interaction(
+    component_global_id: &str,
+    kind: &InteractionKind,
+    context: &TemplateContext,
+    modkey: bool,
+) {
+    let parse_value = |text: &str| match template::render(text, context) {
+        Ok(t) => t,
+        Err(e) => {
+            log_error!("Invalid template `{}`: {}", text, e);
+
+            // Fall back to the old behavior, so the interaction still works
+            text.replace("{value}", &context.value)
+        }
+    };
+
+    match kind {
+        InteractionKind::None() => (),
+        InteractionKind::Command(command, unix_shell, options) => run_command(
+            component_global_id.to_string(),
+            &parse_value(command),
+            unix_shell,
+            CommandOptions {
+                working_directory: parse_value(&options.working_directory),
+                environment: options
+                    .environment
+                    .iter()
+                    .map(|(name, value)| (name.clone(), parse_value(value)))
+                    .collect(),
+                ..options.clone()
+            },
+        ),
+        InteractionKind::Application(app_full_path) => {
+            open_application(&parse_value(app_full_path))
+        }
+        InteractionKind::Website(website_url) => open_website(&parse_value(website_url)),
+        InteractionKind::Shortcut(keys, text, _hold) => simulate_shortcut(keys, &parse_value(text)),
+        InteractionKind::File(file_full_path) => open_file(&parse_value(file_full_path)),
+        InteractionKind::KeyScript(steps, repeat) => run_key_script(
+            RunGuard::new(component_global_id),
+            steps
+                .iter()
+                .map(|step| match step {
+                    KeyStep::Text(text) => KeyStep::Text(parse_value(text)),
+                    _ => step.clone(),
+                })
+                .collect(),
+            *repeat,
+        ),
+        InteractionKind::Http(request) => send_http_request(
+            RunGuard::new(component_global_id),
+            HttpRequest {
+                url: parse_value(&request.url),
+                headers: request
+                    .headers
+                    .iter()
+                    .map(|(name, value)| (name.clone(), parse_value(value)))
+                    .collect(),
+                body: parse_value(&request.body),
+                ..request.clone()
+            },
+        ),
+        InteractionKind::Mqtt(topic, payload, retain) => {
+            mqtt::publish(&parse_value(topic), &parse_value(payload), *retain)
+        }
+        InteractionKind::DBus(call) => dbus::call_method(DBusCall {
+            arguments: call
+                .arguments
+                .iter()
+                .map(|argument| match argument {
+                    DBusArgument::String(text) => DBusArgument::String(parse_value(text)),
+                    _ => argument.clone(),
+                })
+                .collect(),
+            ..call.clone()
+        }),
+        InteractionKind::Media(action, player, report_now_playing) => dbus::control_media_player(
+            action.clone(),
+            player.clone(),
+            context.value.parse::<f64>().unwrap_or(0.0),
+            *report_now_playing,
+        ),
+        InteractionKind::Script(script) => {
+            script::run_script(script.clone(), context.clone(), modkey)
+        }
+        InteractionKind::Clipboard(action) => clipboard::do_clipboard_action(match action {
+            ClipboardAction::Copy(snippet) => ClipboardAction::Copy(parse_value(snippet)),
+            ClipboardAction::Paste(snippet, restore) => {
+                ClipboardAction::Paste(parse_value(snippet), *restore)
+            }
+            ClipboardAction::History(_) => action.clone(),
+        }),
+        InteractionKind::Conditional(condition, then, otherwise) => run_conditional(
+            component_global_id.to_string(),
+            match condition {
+                Condition::FileExists(path) => Condition::FileExists(parse_value(path)),
+                Condition::CommandSucceeds(command, unix_shell) => {
+                    Condition::CommandSucceeds(parse_value(command), unix_shell.clone())
+                }
+                Condition::ComponentValue(id, comparison, value) => {
+                    Condition::ComponentValue(id.clone(), comparison.clone(), parse_value(value))
+                }
+                _ => condition.clone(),
+            },
+            (*then.clone(), *otherwise.clone()),
+            context.clone(),
+            modkey,
+        ),
+        InteractionKind::Plugin(call) => plugin::run_plugin_interaction(
+            &PluginCall {
+                config: call
+                    .config
+                    .iter()
+                    .map(|(name, value)| match value {
+                        serde_json::Value::String(text) => {
+                            (name.clone(), serde_json::Value::String(parse_value(text)))
+                        }
+                        _ => (name.clone(), value.clone()),
+                    })
+                    .collect(),
+                ..call.clone()
+            },
+            context,
+            modkey,
+        ),
+    }
+}
+
+/// Run a component's interaction in the current profile, as if it was used. e.g. from scripts
+pub fn run_component_interaction(component_global_id: &str, modkey: bool) {
+    let interactions = match get_component_interactions(component_global_id.to_string()) {
+        Some(i) => i,
+        None => return,
+    };
+
+    let interaction = if !modkey {
+        &interactions.normal
+    } else {
+        &interactions.modkey
+    };
+
+    // Prevents scripts from running each other endlessly
+    if interaction
+        .kinds()
+        .iter()
+        .any(|kind| matches!(kind, InteractionKind::Script(_)))
+    {
+        log_error!(
+            "Could not run the interaction of `{}`, scripts can't run other scripts!",
+            component_global_id
+        );
+
+        return;
+    }
+
+    // Scripts can't press again or hold the button, so confirmations can't be given
+    if let Err(e) = check_remote_trigger(
+        component_global_id,
+        interaction,
+        &interactions.trigger,
+        false,
+    ) {
+        log_error!("Could not run the interaction: {}", e);
+
+        return;
+    }
+
+    let value = get_component_value(component_global_id).unwrap_or("1".to_string());
+
+    do_interaction(component_global_id, interaction, value, modkey);
+}
+
+/// Run a component's interaction in the current profile with a given value. e.g. from `padpadctl`
+/// Interactions that require a confirmation only run when `confirm` is set
+pub fn trigger_component_interaction(
+    component_global_id: &str,
+    value: Option<String>,
+    modkey: bool,
+    confirm: bool,
+) -> Result<(), String> {
+    let interactions =
+        get_component_interactions(component_global_id.to_string()).ok_or(format!(
+            "Component `{}` has no interaction in the current profile!",
+            component_global_id
+        ))?;
+
+    let interaction = if !modkey {
+        &interactions.normal
+    } else {
+        &interactions.modkey
+    };
+
+    check_remote_trigger(
+        component_global_id,
+        interaction,
+        &interactions.trigger,
+        confirm,
+    )?;
+
+    let value = value
+        .or(get_component_value(component_global_id))
+        .unwrap_or("1".to_string());
+
+    do_interaction(component_global_id, interaction, value, modkey);
+
+    Ok(())
+}
+
+/// Triggers that don't come from the device follow the same `TriggerOptions`, the confirmation
+/// has to be given up front (`confirm`), since they can't press again or hold
+fn check_remote_trigger(
+    component_global_id: &str,
+    interaction: &InteractionKind,
+    options: &TriggerOptions,
+    confirm: bool,
+) -> Result<(), String> {
+    if options.confirmation != Confirmation::None
+        && *interaction != InteractionKind::None()
+        && !confirm
+    {
+        return Err(format!(
+            "Interaction of `{}` requires a confirmation!",
+            component_global_id
+        ));
+    }
+
+    if is_bouncing(component_global_id, options.debounce)
+        || !should_trigger(component_global_id, options)
+    {
+        return Err(format!(
+            "Component `{}` was ignored by its trigger options!",
+            component_global_id
+        ));
+    }
+
+    Ok(())
+}
+
+/// Every call counts as an event, events closer than `debounce` to the previous one are bounces
+fn is_bouncing(component_global_id: &str, debounce: u64) -> bool {
+    let now = Instant::now();
+
+    let last_event = match get_last_events().lock() {
+        Ok(mut last_events) => last_events.insert(component_global_id.to_string(), now),
+        Err(_) => None,
+    };
+
+    last_event
+        .is_some_and(|last_event| now.duration_since(last_event) < Duration::from_millis(debounce))
+}
+
+/// Checks `cooldown` and `ignore_while_running` of the component's `TriggerOptions`, an allowed
+/// trigger starts the next cooldown
+fn should_trigger(component_global_id: &str, options: &TriggerOptions) -> bool {
+    let mut last_triggers = match get_last_triggers().lock() {
+        Ok(l) => l,
+        Err(_) => return true,
+    };
+
+    if !is_trigger_allowed(&last_triggers, component_global_id, options) {
+        return false;
+    }
+
+    last_triggers.insert(component_global_id.to_string(), Instant::now());
+
+    true
+}
+
+/// Same as `should_trigger` without starting the cooldown, e.g. before arming a confirmation
+fn can_trigger(component_global_id: &str, options: &TriggerOptions) -> bool {
+    match get_last_triggers().lock() {
+        Ok(last_triggers) => is_trigger_allowed(&last_triggers, component_global_id, options),
+        Err(_) => true,
+    }
+}
+
+fn is_trigger_allowed(
+    last_triggers: &HashMap<String, Instant>,
+    component_global_id: &str,
+    options: &TriggerOptions,
+) -> bool {
+    if let Some(last_trigger) = last_triggers.get(component_global_id) {
+        if last_trigger.elapsed() < Duration::from_millis(options.cooldown) {
+            return false;
+        }
+    }
+
+    if options.ignore_while_running && is_component_running(component_global_id) {
+        log_info!(
+            "Component `{}` was ignored, its previous run is still active.",
+            component_global_id
+        );
+
+        return false;
+    }
+
+    true
+}
+
+fn arm_interaction(
+    component_global_id: String,
+    interaction: InteractionKind,
+    modkey: bool,
+    options: TriggerOptions,
+) {
+    let arm_id = NEXT_ARM_ID.fetch_add(1, Ordering::Relaxed);
+
+    if let Ok(mut armed_interactions) = get_armed_interactions().lock() {
+        armed_interactions.insert(component_global_id.clone(), (arm_id, interaction, modkey));
+    }
+
+    show_on_display(match options.confirmation {
+        Confirmation::DoublePress(_) => "Press again to confirm",
+        Confirmation::LongPress(_) => "Hold to confirm",
+        _ => "Confirm on screen",
+    });
+
+    if options.armed_led > 0 {
+        set_led(options.armed_led, 255);
+    }
+
+    log_info!(
+        "Interaction of `{}` is armed, waiting for confirmation...",
+        component_global_id
+    );
+
+    let armed_led = options.armed_led;
+
+    // Waiting happens on a separate thread, so serial keeps receiving presses and releases
+    let spawn_result = std::thread::Builder::new()
+        .name("Confirmation".to_string())
+        .spawn(move || {
+            let is_confirmed = match options.confirmation {
+                Confirmation::DoublePress(window) => {
+                    std::thread::sleep(Duration::from_millis(window));
+
+                    false // A second press would've disarmed it already
+                }
+                Confirmation::LongPress(duration) => {
+                    std::thread::sleep(Duration::from_millis(duration));
+
+                    true // A release would've disarmed it already
+                }
+                _ => show_confirmation_prompt(&component_global_id),
+            };
+
+            let armed = disarm_interaction(&component_global_id, Some(arm_id), &options);
+
+            match armed {
+                Some((interaction, modkey)) if is_confirmed => {
+                    run_confirmed_interaction(&component_global_id, &interaction, modkey)
+                }
+                Some(_) => cancel_confirmation(&component_global_id),
+                None => (),
+            }
+        });
+
+    if let Err(e) = spawn_result {
+        log_error!("Failed to spawn `Confirmation` thread: {}", e);
+
+        // Nothing would ever confirm or cancel it
+        if let Ok(mut armed_interactions) = get_armed_interactions().lock() {
+            armed_interactions.retain(|_, (armed_id, _, _)| *armed_id != arm_id);
+        }
+
+        if armed_led > 0 {
+            set_led(armed_led, 0);
+        }
+    }
+}
+
+/// Returns the armed interaction, `arm_id` makes sure a newer arming isn't removed
+fn disarm_interaction(
+    component_global_id: &str,
+    arm_id: Option<u64>,
+    options: &TriggerOptions,
+) -> Option<(InteractionKind, bool /* modkey */)> {
+    let mut armed_interactions = get_armed_interactions().lock().ok()?;
+
+    match armed_interactions.get(component_global_id) {
+        Some((armed_id, _, _)) if arm_id.is_none() || arm_id == Some(*armed_id) => (),
+        _ => return None,
+    }
+
+    if options.armed_led > 0 {
+        set_led(options.armed_led, 0);
+    }
+
+    armed_interactions
+        .remove(component_global_id)
+        .map(|(_, interaction, modkey)| (interaction, modkey))
+}
+
+fn is_armed(component_global_id: &str) -> bool {
+    get_armed_interactions()
+        .lock()
+        .map(|armed_interactions| armed_interactions.contains_key(component_global_id))
+        .unwrap_or(false)
+}
+
+/// Confirmed interactions follow the same `TriggerOptions` as presses without a confirmation
+fn run_confirmed_interaction(
+    component_global_id: &str,
+    interaction: &InteractionKind,
+    modkey: bool,
+) {
+    let options = get_component_interactions(component_global_id.to_string())
+        .map(|interactions| interactions.trigger)
+        .unwrap_or_default();
+
+    if !should_trigger(component_global_id, &options) {
+        show_on_display("Ignored");
+
+        log_info!(
+            "Interaction of `{}` was confirmed, but its trigger options ignored it.",
+            component_global_id
+        );
+
+        return;
+    }
+
+    show_on_display("Confirmed");
+
+    log_info!("Interaction of `{}` was confirmed.", component_global_id);
+
+    run_button_interaction(component_global_id, interaction, 1, modkey);
+}
+
+fn run_button_interaction(
+    component_global_id: &str,
+    interaction: &InteractionKind,
+    value: i8,
+    modkey: bool,
+) {
+    // A prompt can be confirmed after the button was released, nothing would release the keys
+    let is_pressed = get_component_value(component_global_id).as_deref() == Some("1");
+
+    match interaction {
+        // `hold` only works with keys, text is typed as usual
+        InteractionKind::Shortcut(keys, text, true) if text.is_empty() && is_pressed => {
+            press_held_keys(component_global_id.to_string(), keys)
+        }
+        _ => do_interaction(component_global_id, interaction, value, modkey),
+    }
+}
+
+fn cancel_confirmation(component_global_id: &str) {
+    show_on_display("Cancelled");
+
+    log_info!("Interaction of `{}` was cancelled.", component_global_id);
+}
+
+/// Blocks until the dialog is answered, returns `true` if it was confirmed
+fn show_confirmation_prompt(component_global_id: &str) -> bool {
+    let message = format!("Run the interaction of {}?", component_global_id);
+
+    let status = if cfg!(target_os = "windows") {
+        Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                &format!(
+                    "Add-Type -AssemblyName System.Windows.Forms; \
+                    if ([System.Windows.Forms.MessageBox]::Show('{}', '{}', 'YesNo') -ne 'Yes') \
+                    {{ exit 1 }}",
+                    message, APP_NAME
+                ),
+            ])
+            .status()
+    } else if cfg!(target_os = "macos") {
+        Command::new("osascript")
+            .args([
+                "-e",
+                &format!("display dialog \"{}\" with title \"{}\"", message, APP_NAME),
+            ])
+            .status()
+    } else {
+        // `zenity` on GNOME and most desktops, `kdialog` on KDE
+        Command::new("zenity")
+            .args(["--question", "--title", APP_NAME, "--text", &message])
+            .status()
+            .or_else(|_| {
+                Command::new("kdialog")
+                    .args(["--title", APP_NAME, "--yesno", &message])
+                    .status()
+            })
+    };
+
+    match status {
+        Ok(s) => s.success(),
+        Err(e) => {
+            log_error!("Could not show the confirmation prompt: {}", e);
+
+            false
+        }
+    }
+}
+
+fn get_armed_interactions() -> &'static Mutex<HashMap<String, ArmedInteraction>> {
+    ARMED_INTERACTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn is_component_running(component_global_id: &str) -> bool {
+    let is_interaction_running = get_running_interactions()
+        .lock()
+        .map(|running_interactions| running_interactions.contains_key(component_global_id))
+        .unwrap_or(false);
+
+    let is_command_running = get_running_commands()
+        .lock()
+        .map(|running_commands| {
+            running_commands
+                .values()
+                .any(|(id, _)| id == component_global_id)
+        })
+        .unwrap_or(false);
+
+    is_interaction_running || is_command_running
+}
+
+fn get_last_events() -> &'static Mutex<HashMap<String, Instant>> {
+    LAST_EVENTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_last_triggers() -> &'static Mutex<HashMap<String, Instant>> {
+    LAST_TRIGGERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_deferred_values() -> &'static Mutex<HashMap<String, (u8, Instant)>> {
+    DEFERRED_VALUES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_running_interactions() -> &'static Mutex<HashMap<String, usize>> {
+    RUNNING_INTERACTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_template_context(component_global_id: &str, value: String) -> TemplateContext {
+    let profile = {
+        let config = CONFIG
+            .get()
+            .expect("Could not retrieve CONFIG data!")
+            .lock()
+            .unwrap();
+
+        config.profiles[config.settings.current_profile]
+            .name
+            .clone()
+    };
+
+    let prev_value = match get_last_values().lock() {
+        Ok(mut last_values) => last_values
+            .insert(component_global_id.to_string(), value.clone())
+            .unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+
+    TemplateContext {
+        component_global_id: component_global_id.to_string(),
+        profile,
+        value,
+        prev_value,
+    }
+}
+
+/// Returns the last value that was received from the component. e.g. `Potentiometer:1` => `50`
+pub fn get_component_value(component_global_id: &str) -> Option<String> {
+    get_component_values()
+        .lock()
+        .ok()?
+        .get(component_global_id)
+        .cloned()
+}
+
+/// Last value of every component that was used since the `Service` started
+pub fn get_all_component_values() -> HashMap<String, String> {
+    get_component_values()
+        .lock()
+        .map(|values| values.clone())
+        .unwrap_or_default()
+}
+
+fn get_component_values() -> &'static Mutex<HashMap<String, String>> {
+    COMPONENT_VALUES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_last_values() -> &'static Mutex<HashMap<String, String>> {
+    LAST_VALUES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn record_component_value(component_global_id: String, value: String) {
+    if let Ok(mut component_values) = get_component_values().lock() {
+        component_values.insert(component_global_id.clone(), value.clone());
+    }
+
+    bus::publish(ServiceEvent::Interaction(InteractionEvent::ComponentValue(
+        component_global_id,
+        value,
+    )));
+}
+
+fn get_component_interactions(component_global_id: String) -> Option<Interaction> {
+    let config = CONFIG
+        .get()
+        .expect("Could not retrieve CONFIG data!")
+        .lock()
+        .unwrap();
+
+    let current_profile = &config.profiles[config.settings.current_profile];
+
+    let interactions = current_profile
+        .interactions
+        .get(&component_global_id)
+        .cloned();
+
+    if interactions.is_none() {
         log_error!(
             "Couldn't find any interaction for the Component `{}` in the current profile `{}`",
             component_global_id,
@@ -186,18 +1815,84 @@ fn get_component_interactions(component_global_id: String) -> Option<Interaction
     interactions
 }
 
-pub fn do_button(id: u8, value: i8, modkey: bool, _serial: &mut Serial) {
+/// Run the interactions of device events and simulated ones (IPC `simulate`) in order
+pub fn handle_interaction_thread(events: mpsc::Receiver<ServiceEvent>) {
+    for event in events {
+        if let ServiceEvent::Device(DeviceEvent::Input(kind, id, value, modkey)) = event {
+            // A panicking interaction shouldn't stop the ones that come after it
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                dispatch_component_event(&kind, id, &value, modkey)
+            }));
+
+            if result.is_err() {
+                log_error!("Interaction of `{}:{}` has panicked!", kind, id);
+            }
+        }
+    }
+}
+
+fn dispatch_component_event(kind: &ComponentKind, id: u8, value: &str, modkey: bool) {
+    match kind {
+        ComponentKind::Button => do_button(id, value.parse::<i8>().unwrap_or(0), modkey),
+        ComponentKind::Potentiometer => do_potentiometer(id, value.parse::<u8>().unwrap_or(0)),
+        _ => (),
+    }
+}
+
+pub fn do_button(id: u8, value: i8, modkey: bool) {
     let component_global_id = format!("{}:{}", ComponentKind::Button, id);
 
-    update_server_data_component(component_global_id.clone(), value.to_string());
+    record_component_value(component_global_id.clone(), value.to_string());
+
+    // Button was released
+    if value == 0 {
+        release_held_keys(&component_global_id);
+
+        // Releasing too early cancels a long press, bounces while holding are ignored
+        if is_armed(&component_global_id) {
+            let options = get_component_interactions(component_global_id.clone())
+                .map(|interactions| interactions.trigger)
+                .unwrap_or_default();
+
+            if matches!(options.confirmation, Confirmation::LongPress(_))
+                && !is_bouncing(&component_global_id, options.debounce)
+                && disarm_interaction(&component_global_id, None, &options).is_some()
+            {
+                cancel_confirmation(&component_global_id);
+            }
+        }
+
+        return;
+    }
 
     // Only on button press for now
     if value != 1 {
         return;
     }
 
-    let interactions =
-        get_component_interactions(component_global_id).unwrap_or(Interaction::default());
+    let interactions = get_component_interactions(component_global_id.clone()).unwrap_or_default();
+
+    let options = &interactions.trigger;
+
+    if is_bouncing(&component_global_id, options.debounce) {
+        return;
+    }
+
+    // The second press of a double press confirms the armed interaction
+    if let Confirmation::DoublePress(_) = options.confirmation {
+        if let Some((interaction, armed_modkey)) =
+            disarm_interaction(&component_global_id, None, options)
+        {
+            run_confirmed_interaction(&component_global_id, &interaction, armed_modkey);
+
+            return;
+        }
+    }
+
+    // Still waiting for a long press or a prompt
+    if is_armed(&component_global_id) {
+        return;
+    }
 
     let interaction = if !modkey {
         &interactions.normal
@@ -205,7 +1900,25 @@ pub fn do_button(id: u8, value: i8, modkey: bool, _serial: &mut Serial) {
         &interactions.modkey
     };
 
-    do_interaction(interaction, value);
+    // The cooldown starts once it's confirmed, but there's no point in asking if it'd be ignored
+    if options.confirmation != Confirmation::None && *interaction != InteractionKind::None() {
+        if can_trigger(&component_global_id, options) {
+            arm_interaction(
+                component_global_id,
+                interaction.clone(),
+                modkey,
+                options.clone(),
+            );
+        }
+
+        return;
+    }
+
+    if !should_trigger(&component_global_id, options) {
+        return;
+    }
+
+    run_button_interaction(&component_global_id, interaction, value, modkey);
 }
 
 pub fn do_potentiometer(
@@ -214,14 +1927,296 @@ pub fn do_potentiometer(
 ) {
     let component_global_id = format!("{}:{}", ComponentKind::Potentiometer, id);
 
-    update_server_data_component(component_global_id.clone(), value.to_string());
+    record_component_value(component_global_id.clone(), value.to_string());
 
-    let interactions = get_component_interactions(component_global_id).unwrap_or(Interaction {
-        normal: InteractionKind::None(),
-        modkey: InteractionKind::None(),
-    });
+    let options = get_component_interactions(component_global_id.clone())
+        .map(|interactions| interactions.trigger)
+        .unwrap_or_default();
+
+    if is_bouncing(&component_global_id, options.debounce) {
+        defer_potentiometer_value(&component_global_id, id, value, &options);
+
+        return;
+    }
+
+    trigger_potentiometer(id, value);
+}
+
+fn trigger_potentiometer(id: u8, value: u8) {
+    let component_global_id = format!("{}:{}", ComponentKind::Potentiometer, id);
+
+    let interactions =
+        get_component_interactions(component_global_id.clone()).unwrap_or(Interaction {
+            normal: InteractionKind::None(),
+            modkey: InteractionKind::None(),
+            trigger: TriggerOptions::default(),
+        });
+
+    if !should_trigger(&component_global_id, &interactions.trigger) {
+        defer_potentiometer_value(&component_global_id, id, value, &interactions.trigger);
+
+        return;
+    }
+
+    // This value is newer than the one that was waiting
+    if let Ok(mut deferred_values) = get_deferred_values().lock() {
+        deferred_values.remove(&component_global_id);
+    }
 
     let interaction = &interactions.normal;
 
-    do_interaction(interaction, value);
+    do_interaction(&component_global_id, interaction, value, false);
+}
+
+/// The last suppressed value is applied once `debounce` and `cooldown` are over, so turning a
+/// potentiometer quickly still ends on its final position
+fn defer_potentiometer_value(
+    component_global_id: &str,
+    id: u8,
+    value: u8,
+    options: &TriggerOptions,
+) {
+    let now = Instant::now();
+
+    let cooldown_end = get_last_triggers()
+        .lock()
+        .ok()
+        .and_then(|last_triggers| last_triggers.get(component_global_id).copied())
+        .map(|last_trigger| last_trigger + Duration::from_millis(options.cooldown))
+        .unwrap_or(now);
+
+    let deadline = cooldown_end.max(now + Duration::from_millis(options.debounce));
+
+    // Only `ignore_while_running` suppressed it, it's ignored as usual
+    if deadline <= now {
+        return;
+    }
+
+    let is_scheduled = match get_deferred_values().lock() {
+        Ok(mut deferred_values) => deferred_values
+            .insert(component_global_id.to_string(), (value, deadline))
+            .is_some(),
+        Err(_) => return,
+    };
+
+    if is_scheduled {
+        return;
+    }
+
+    let deferred_id = component_global_id.to_string();
+
+    let spawn_result = std::thread::Builder::new()
+        .name("Deferred Value".to_string())
+        .spawn(move || loop {
+            let mut deferred_values = match get_deferred_values().lock() {
+                Ok(d) => d,
+                Err(_) => return,
+            };
+
+            // Newer values move the deadline, a trigger in the meantime removes it
+            let (value, deadline) = match deferred_values.get(&deferred_id) {
+                Some(d) => *d,
+                None => return,
+            };
+
+            let now = Instant::now();
+
+            if deadline > now {
+                drop(deferred_values);
+
+                std::thread::sleep(deadline - now);
+
+                continue;
+            }
+
+            deferred_values.remove(&deferred_id);
+
+            drop(deferred_values);
+
+            trigger_potentiometer(id, value);
+
+            return;
+        });
+
+    if let Err(e) = spawn_result {
+        log_error!("Failed to spawn `Deferred Value` thread: {}", e);
+
+        if let Ok(mut deferred_values) = get_deferred_values().lock() {
+            deferred_values.remove(component_global_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    fn http_request() -> HttpRequest {
+        HttpRequest {
+            method: " post ".to_string(),
+            url: " http://127.0.0.1:8080/hook?volume=40 ".to_string(),
+            headers: vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                (" ".to_string(), "ignored".to_string()),
+                (" X-Padpad ".to_string(), "1".to_string()),
+            ],
+            body: "{\"volume\": 40}".to_string(),
+            timeout: 1000,
+            response: HttpResponseAction::Display,
+        }
+    }
+
+    #[test]
+    fn builds_http_request() {
+        let request = build_http_request(&http_request());
+
+        assert_eq!(request.method(), "POST");
+        assert_eq!(request.url(), "http://127.0.0.1:8080/hook?volume=40");
+        assert_eq!(request.header("Content-Type"), Some("application/json"));
+        assert_eq!(request.header("X-Padpad"), Some("1"));
+        assert_eq!(request.header_names().len(), 2);
+    }
+
+    #[test]
+    fn serializes_http_request() {
+        let interaction = InteractionKind::Http(http_request());
+
+        let toml_str = toml::to_string(&interaction).unwrap();
+
+        assert!(toml_str.contains("response = \"Display\""));
+        assert_eq!(
+            toml::from_str::<InteractionKind>(&toml_str).unwrap(),
+            interaction
+        );
+    }
+
+    #[test]
+    fn sends_http_request_to_local_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            let mut content_length = 0;
+
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("Content-Length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+
+                head.push_str(&line);
+
+                if line == "\r\n" {
+                    break;
+                }
+            }
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        let status = perform_http_request(&HttpRequest {
+            url: format!("http://127.0.0.1:{}/hook?volume=40", port),
+            ..http_request()
+        });
+
+        let (head, body) = server.join().unwrap();
+
+        assert_eq!(status, "201 Created");
+        assert!(head.starts_with("POST /hook?volume=40 HTTP/1.1\r\n"));
+        assert!(head.contains("X-Padpad: 1\r\n"));
+        assert_eq!(body, "{\"volume\": 40}");
+    }
+
+    #[test]
+    fn debounces_events() {
+        assert!(!is_bouncing("test_debounce", 1000));
+        assert!(is_bouncing("test_debounce", 1000));
+        assert!(!is_bouncing("test_debounce", 0));
+        assert!(!is_bouncing("test_debounce_other", 1000));
+    }
+
+    #[test]
+    fn allows_triggers_after_cooldown() {
+        let options = TriggerOptions {
+            cooldown: 1000,
+            ..TriggerOptions::default()
+        };
+        let mut last_triggers = HashMap::new();
+
+        assert!(is_trigger_allowed(
+            &last_triggers,
+            "test_cooldown",
+            &options
+        ));
+
+        last_triggers.insert("test_cooldown".to_string(), Instant::now());
+
+        assert!(!is_trigger_allowed(
+            &last_triggers,
+            "test_cooldown",
+            &options
+        ));
+        assert!(is_trigger_allowed(
+            &last_triggers,
+            "test_cooldown",
+            &TriggerOptions::default()
+        ));
+
+        last_triggers.insert(
+            "test_cooldown".to_string(),
+            Instant::now() - Duration::from_millis(1500),
+        );
+
+        assert!(is_trigger_allowed(
+            &last_triggers,
+            "test_cooldown",
+            &options
+        ));
+    }
+
+    #[test]
+    fn ignores_triggers_while_running() {
+        let options = TriggerOptions {
+            ignore_while_running: true,
+            ..TriggerOptions::default()
+        };
+        let last_triggers = HashMap::new();
+
+        let run_guard = RunGuard::new("test_running");
+
+        assert!(!is_trigger_allowed(
+            &last_triggers,
+            "test_running",
+            &options
+        ));
+        assert!(is_trigger_allowed(
+            &last_triggers,
+            "test_running",
+            &TriggerOptions::default()
+        ));
+
+        drop(run_guard);
+
+        assert!(is_trigger_allowed(&last_triggers, "test_running", &options));
+    }
 }
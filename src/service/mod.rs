@@ -1,3 +1,16 @@
+pub mod api;
+pub mod bus;
+pub mod clipboard;
+pub mod dbus;
+pub mod focus;
+pub mod instance;
 pub mod interaction;
+pub mod mqtt;
+pub mod plugin;
+pub mod profile;
+pub mod schedule;
+pub mod script;
 pub mod serial;
+pub mod shutdown;
+pub mod template;
 pub mod tray;
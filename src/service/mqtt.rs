@@ -0,0 +1,329 @@
+use std::sync::{Mutex, OnceLock};
+
+use rumqttc::{Client, Event, MqttOptions, Packet, QoS};
+
+use crate::{
+    config::{MqttSettings, MqttSubscriptionAction, CONFIG},
+    constants::{MQTT_KEEP_ALIVE, MQTT_RECONNECT_INTERVAL},
+    log_error, log_info, log_print, log_warn,
+    service::{
+        profile::switch_profile_by_name,
+        serial::{set_led, show_on_display},
+        shutdown::is_shutting_down,
+    },
+};
+
+static MQTT_CLIENT: OnceLock<Mutex<Option<Client>>> = OnceLock::new();
+
+pub fn handle_mqtt_thread() {
+    let settings = {
+        let config = CONFIG
+            .get()
+            .expect("Could not retrieve CONFIG data!")
+            .lock()
+            .unwrap();
+
+        config.settings.mqtt.clone()
+    };
+
+    if !settings.enabled {
+        log_info!("MQTT is disabled in the config file.");
+
+        return;
+    }
+
+    let (client, mut connection) = Client::new(build_options(&settings), 16);
+
+    if let Ok(mut mqtt_client) = get_mqtt_client().lock() {
+        *mqtt_client = Some(client.clone());
+    }
+
+    for event in connection.iter() {
+        if is_shutting_down() {
+            break;
+        }
+
+        match event {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                log_info!(
+                    "Connected to the MQTT broker at `{}:{}`",
+                    settings.host,
+                    settings.port
+                );
+
+                // Subscriptions are lost on reconnection, so they're requested every time
+                subscribe(&client, &settings);
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                let payload = String::from_utf8_lossy(&publish.payload).to_string();
+
+                log_print!(
+                    "[MQTT] topic: {} | payload: {}",
+                    publish.topic,
+                    payload.trim()
+                );
+
+                handle_incoming_message(&settings, &publish.topic, payload.trim());
+            }
+            Ok(_) => (),
+            Err(e) => {
+                log_warn!("MQTT connection error: {}, retrying...", e);
+
+                std::thread::sleep(std::time::Duration::from_millis(MQTT_RECONNECT_INTERVAL));
+            }
+        }
+    }
+}
+
+fn build_options(settings: &MqttSettings) -> MqttOptions {
+    let mut options = MqttOptions::new(&settings.client_id, &settings.host, settings.port);
+
+    options.set_keep_alive(std::time::Duration::from_secs(MQTT_KEEP_ALIVE));
+
+    if !settings.username.is_empty() {
+        options.set_credentials(&settings.username, &settings.password);
+    }
+
+    options
+}
+
+fn subscribe(client: &Client, settings: &MqttSettings) {
+    for subscription in &settings.subscriptions {
+        if let Err(e) = client.try_subscribe(&subscription.topic, QoS::AtMostOnce) {
+            log_error!(
+                "Failed to subscribe to MQTT topic `{}`: {}",
+                subscription.topic,
+                e
+            );
+        }
+    }
+}
+
+fn handle_incoming_message(settings: &MqttSettings, topic: &str, payload: &str) {
+    for action in get_subscription_actions(settings, topic) {
+        match action {
+            MqttSubscriptionAction::Led(id) => set_led(*id, parse_led_value(payload)),
+            MqttSubscriptionAction::Display => show_on_display(payload),
+            MqttSubscriptionAction::Profile => {
+                switch_profile_by_name(payload);
+            }
+        }
+    }
+}
+
+/// Actions of every subscription whose topic filter matches `topic`
+fn get_subscription_actions<'a>(
+    settings: &'a MqttSettings,
+    topic: &str,
+) -> Vec<&'a MqttSubscriptionAction> {
+    settings
+        .subscriptions
+        .iter()
+        .filter(|subscription| rumqttc::matches(topic, &subscription.topic))
+        .map(|subscription| &subscription.action)
+        .collect()
+}
+
+fn parse_led_value(payload: &str) -> u8 {
+    match payload.to_lowercase().as_str() {
+        "on" | "true" => 255,
+        "off" | "false" => 0,
+        value => value.parse::<u8>().unwrap_or(0),
+    }
+}
+
+/// Only queues the message, so it never blocks the interactions while the broker is away
+pub fn publish(topic: &str, payload: &str, retain: bool) {
+    let topic = topic.trim();
+
+    let client = match get_mqtt_client().lock() {
+        Ok(client) => client.clone(),
+        Err(_) => None,
+    };
+
+    let client = match client {
+        Some(c) => c,
+        None => {
+            log_error!(
+                "Could not publish to MQTT topic `{}`, MQTT isn't enabled!",
+                topic
+            );
+
+            return;
+        }
+    };
+
+    match client.try_publish(topic, QoS::AtLeastOnce, retain, payload.as_bytes()) {
+        Ok(_) => log_info!("MQTT message published to `{}`", topic),
+        Err(e) => log_error!("Failed to publish to MQTT topic `{}`: {}", topic, e),
+    }
+}
+
+/// Tell the broker we're leaving, so the connection isn't treated as lost
+pub fn disconnect() {
+    if let Ok(mqtt_client) = get_mqtt_client().lock() {
+        if let Some(client) = mqtt_client.as_ref() {
+            client.try_disconnect().ok();
+        }
+    }
+}
+
+fn get_mqtt_client() -> &'static Mutex<Option<Client>> {
+    MQTT_CLIENT.get_or_init(|| Mutex::new(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::config::MqttSubscription;
+
+    fn settings() -> MqttSettings {
+        MqttSettings {
+            enabled: true,
+            client_id: format!("padpad-test-{}", std::process::id()),
+            subscriptions: vec![
+                MqttSubscription {
+                    topic: "home/+/state".to_string(),
+                    action: MqttSubscriptionAction::Led(3),
+                },
+                MqttSubscription {
+                    topic: "padpad/#".to_string(),
+                    action: MqttSubscriptionAction::Display,
+                },
+                MqttSubscription {
+                    topic: "padpad/profile".to_string(),
+                    action: MqttSubscriptionAction::Profile,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builds_options() {
+        let mut settings = settings();
+
+        let options = build_options(&settings);
+
+        assert_eq!(options.client_id(), settings.client_id);
+        assert_eq!(options.broker_address(), ("localhost".to_string(), 1883));
+        assert_eq!(options.keep_alive(), Duration::from_secs(MQTT_KEEP_ALIVE));
+        assert_eq!(options.credentials(), None);
+
+        settings.username = "pad".to_string();
+        settings.password = "secret".to_string();
+
+        assert_eq!(
+            build_options(&settings).credentials(),
+            Some(("pad".to_string(), "secret".to_string()))
+        );
+    }
+
+    #[test]
+    fn matches_subscriptions() {
+        let settings = settings();
+
+        assert_eq!(
+            get_subscription_actions(&settings, "home/kitchen/state"),
+            vec![&MqttSubscriptionAction::Led(3)]
+        );
+        assert_eq!(
+            get_subscription_actions(&settings, "padpad/profile"),
+            vec![
+                &MqttSubscriptionAction::Display,
+                &MqttSubscriptionAction::Profile
+            ]
+        );
+        assert!(get_subscription_actions(&settings, "home/kitchen/light/state").is_empty());
+    }
+
+    #[test]
+    fn parses_led_values() {
+        assert_eq!(parse_led_value("ON"), 255);
+        assert_eq!(parse_led_value("true"), 255);
+        assert_eq!(parse_led_value("off"), 0);
+        assert_eq!(parse_led_value("128"), 128);
+        assert_eq!(parse_led_value("300"), 0);
+        assert_eq!(parse_led_value("dim"), 0);
+    }
+
+    #[test]
+    fn serializes_settings() {
+        let settings = settings();
+
+        let toml_str = toml::to_string(&settings).unwrap();
+        let parsed = toml::from_str::<MqttSettings>(&toml_str).unwrap();
+
+        assert_eq!(parsed.client_id, settings.client_id);
+        assert_eq!(parsed.port, settings.port);
+        assert_eq!(
+            parsed
+                .subscriptions
+                .iter()
+                .map(|subscription| (subscription.topic.as_str(), &subscription.action))
+                .collect::<Vec<_>>(),
+            settings
+                .subscriptions
+                .iter()
+                .map(|subscription| (subscription.topic.as_str(), &subscription.action))
+                .collect::<Vec<_>>()
+        );
+
+        // The format documented in the README
+        let parsed = toml::from_str::<MqttSettings>(
+            "[[subscriptions]]\ntopic = \"home/office/light\"\naction = { Led = 1 }",
+        )
+        .unwrap();
+
+        assert_eq!(parsed.host, "localhost");
+        assert_eq!(
+            parsed.subscriptions[0].action,
+            MqttSubscriptionAction::Led(1)
+        );
+    }
+
+    #[test]
+    #[ignore = "needs an MQTT broker on localhost:1883, e.g. `mosquitto`"]
+    fn round_trips_message_through_local_broker() {
+        let settings = settings();
+        let topic = format!("padpad/test/{}", std::process::id());
+
+        let (client, mut connection) = Client::new(build_options(&settings), 16);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut published = false;
+
+        while Instant::now() < deadline {
+            let event = match connection.recv_timeout(Duration::from_millis(100)) {
+                Ok(event) => event.expect("Could not connect to the MQTT broker"),
+                Err(_) => continue,
+            };
+
+            match event {
+                Event::Incoming(Packet::ConnAck(_)) => subscribe(&client, &settings),
+                // One `SubAck` arrives for every subscription, the message is only sent once
+                Event::Incoming(Packet::SubAck(_)) if !published => {
+                    client
+                        .try_publish(&topic, QoS::AtLeastOnce, false, "Hello")
+                        .unwrap();
+
+                    published = true;
+                }
+                Event::Incoming(Packet::Publish(publish)) => {
+                    assert_eq!(publish.topic, topic);
+                    assert_eq!(publish.payload.as_ref(), b"Hello");
+                    assert_eq!(
+                        get_subscription_actions(&settings, &publish.topic),
+                        vec![&MqttSubscriptionAction::Display]
+                    );
+
+                    return;
+                }
+                _ => (),
+            }
+        }
+
+        panic!("The published message wasn't received in time!");
+    }
+}
@@ -0,0 +1,421 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Mutex, OnceLock,
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    config::CONFIG,
+    constants::{PLUGINS_DIRECTORY_NAME, PLUGIN_TIMEOUT},
+    log_error, log_info,
+    service::{
+        interaction::PluginCall,
+        profile::switch_profile_by_name,
+        serial::{set_led, show_on_display},
+        template::TemplateContext,
+    },
+};
+
+static PLUGINS: OnceLock<Mutex<Vec<Plugin>>> = OnceLock::new();
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1); // `0` is used by `describe`
+
+/// What a plugin provides, it's the result of the plugin's `describe` method
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginDescription {
+    pub name: String, // Used by `PluginCall`. e.g. `obs`
+    pub version: String,
+    pub interactions: Vec<PluginInteraction>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginInteraction {
+    pub id: String,   // Sent back to the plugin. e.g. `switch_scene`
+    pub name: String, // Shown in the `Dashboard`. e.g. `Switch Scene`
+    pub fields: Vec<PluginField>,
+}
+
+/// A config field of a plugin interaction, the `Dashboard` shows a form based on these
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginField {
+    pub name: String, // Key in the interaction's `config`
+    pub label: String,
+    #[serde(rename = "type")]
+    pub kind: PluginFieldKind,
+    pub default: Value,
+    pub choices: Vec<String>, // Only used by `choice` fields
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginFieldKind {
+    #[default]
+    String,
+    Number,
+    Boolean,
+    Choice,
+}
+
+struct Plugin {
+    description: PluginDescription,
+    process: Child,
+    requests: mpsc::Sender<Value>, // Written to the plugin's stdin by its own thread
+}
+
+/// Start every executable in the plugins directory and ask what it provides
+pub fn load_plugins() {
+    let folder = get_plugins_path();
+
+    let entries = match std::fs::read_dir(&folder) {
+        Ok(e) => e,
+        Err(_) => return, // There's no plugins directory
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_executable(path))
+        .collect();
+
+    paths.sort();
+
+    // Every plugin is described at the same time, a slow one doesn't delay the others
+    let results: Vec<Result<Plugin, String>> = std::thread::scope(|scope| {
+        let starts: Vec<_> = paths
+            .iter()
+            .map(|path| scope.spawn(|| start_plugin(path)))
+            .collect();
+
+        starts
+            .into_iter()
+            .map(|start| {
+                start
+                    .join()
+                    .unwrap_or(Err("Plugin loading has panicked!".to_string()))
+            })
+            .collect()
+    });
+
+    // Registered in the order of their paths, so the same plugin wins a name conflict every time
+    for (path, result) in paths.iter().zip(results) {
+        let mut plugin = match result {
+            Ok(p) => p,
+            Err(e) => {
+                log_error!("Failed to load plugin `{}`: {}", path.display(), e);
+
+                continue;
+            }
+        };
+
+        let mut plugins = match get_plugins().lock() {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+
+        if plugins
+            .iter()
+            .any(|p| p.description.name == plugin.description.name)
+        {
+            log_error!(
+                "Plugin `{}` from `{}` was ignored, a plugin with the same name is already loaded!",
+                plugin.description.name,
+                path.display()
+            );
+
+            stop_process(&mut plugin.process);
+
+            continue;
+        }
+
+        log_info!(
+            "Plugin `{}` {} loaded with {} interaction(s).",
+            plugin.description.name,
+            plugin.description.version,
+            plugin.description.interactions.len()
+        );
+
+        plugins.push(plugin);
+    }
+}
+
+/// Stop every plugin process, e.g. when the `Service` is closing
+pub fn unload_plugins() {
+    let plugins: Vec<Plugin> = match get_plugins().lock() {
+        Ok(mut p) => p.drain(..).collect(),
+        Err(_) => return,
+    };
+
+    for mut plugin in plugins {
+        stop_process(&mut plugin.process);
+
+        log_info!("Plugin `{}` was stopped.", plugin.description.name);
+    }
+}
+
+/// Descriptions of the loaded plugins, requested by the `Dashboard`
+pub fn get_plugin_descriptions() -> Vec<PluginDescription> {
+    match get_plugins().lock() {
+        Ok(plugins) => plugins.iter().map(|p| p.description.clone()).collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// The request is written by the plugin's writer thread and the response is handled by its
+/// reader thread, so a plugin that stopped reading doesn't block anything
+pub fn run_plugin_interaction(call: &PluginCall, context: &TemplateContext, modkey: bool) {
+    let requests = match get_plugins().lock() {
+        Ok(plugins) => plugins
+            .iter()
+            .find(|p| p.description.name == call.plugin)
+            .map(|p| p.requests.clone()),
+        Err(_) => return,
+    };
+
+    let requests = match requests {
+        Some(r) => r,
+        None => {
+            log_error!("Plugin `{}` isn't loaded!", call.plugin);
+
+            return;
+        }
+    };
+
+    let params = json!({
+        "interaction": call.interaction,
+        "config": call.config,
+        "component": context.component_global_id,
+        "value": context.value,
+        "prev_value": context.prev_value,
+        "profile": context.profile,
+        "modkey": modkey,
+    });
+
+    let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+
+    match requests.send(request_message(id, "run", params)) {
+        Ok(_) => log_info!(
+            "Plugin interaction `{}/{}` was sent.",
+            call.plugin,
+            call.interaction
+        ),
+        Err(_) => log_error!(
+            "Could not send a request to plugin `{}`, it has exited!",
+            call.plugin
+        ),
+    }
+}
+
+fn start_plugin(path: &Path) -> Result<Plugin, String> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut process = Command::new(path)
+        .current_dir(path.parent().unwrap_or(Path::new(".")))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    let (stdin, stdout) = match (process.stdin.take(), process.stdout.take()) {
+        (Some(stdin), Some(stdout)) => (stdin, stdout),
+        _ => {
+            stop_process(&mut process);
+
+            return Err("Could not open the plugin's stdin/stdout!".to_string());
+        }
+    };
+
+    let (requests, requests_receiver) = mpsc::channel();
+    let (describe_sender, describe_receiver) = mpsc::channel();
+
+    let writer_name = file_name.clone();
+    let reader_name = file_name.clone();
+
+    let writer = std::thread::Builder::new()
+        .name("Plugin writer".to_string())
+        .spawn(move || handle_plugin_writer(&writer_name, stdin, requests_receiver));
+
+    if let Err(e) = writer {
+        stop_process(&mut process);
+
+        return Err(e.to_string());
+    }
+
+    let reader = std::thread::Builder::new()
+        .name("Plugin".to_string())
+        .spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let message: Value = match line.map(|l| serde_json::from_str(&l)) {
+                    Ok(Ok(m)) => m,
+                    Ok(Err(e)) => {
+                        log_error!("Plugin `{}` sent invalid JSON: {}", reader_name, e);
+
+                        continue;
+                    }
+                    Err(_) => break,
+                };
+
+                if message["method"].is_string() {
+                    handle_plugin_message(&reader_name, &message);
+                } else if message["id"] == 0 {
+                    describe_sender.send(message).ok();
+                } else if !message["error"].is_null() {
+                    log_error!(
+                        "Plugin `{}` failed: {}",
+                        reader_name,
+                        message["error"]["message"]
+                            .as_str()
+                            .unwrap_or(&message["error"].to_string())
+                    );
+                }
+            }
+
+            log_info!("Plugin `{}` exited.", reader_name);
+        });
+
+    if let Err(e) = reader {
+        stop_process(&mut process);
+
+        return Err(e.to_string());
+    }
+
+    let description = requests
+        .send(request_message(0, "describe", json!({})))
+        .map_err(|_| "The plugin has exited!".to_string())
+        .and_then(|_| {
+            describe_receiver
+                .recv_timeout(Duration::from_millis(PLUGIN_TIMEOUT))
+                .map_err(|_| "`describe` didn't respond in time!".to_string())
+        })
+        .and_then(|response| {
+            serde_json::from_value::<PluginDescription>(response["result"].clone())
+                .map_err(|e| format!("Invalid `describe` result: {}", e))
+        });
+
+    let mut description = match description {
+        Ok(d) => d,
+        Err(e) => {
+            stop_process(&mut process);
+
+            return Err(e);
+        }
+    };
+
+    if description.name.trim().is_empty() {
+        description.name = file_name;
+    }
+
+    Ok(Plugin {
+        description,
+        process,
+        requests,
+    })
+}
+
+/// Plugins can send these notifications at any time, they don't get a response
+fn handle_plugin_message(plugin_name: &str, message: &Value) {
+    let params = &message["params"];
+
+    match message["method"].as_str().unwrap_or_default() {
+        "log" => log_info!(
+            "[Plugin {}] {}",
+            plugin_name,
+            params["message"].as_str().unwrap_or_default()
+        ),
+        "display" => show_on_display(params["text"].as_str().unwrap_or_default()),
+        "set_led" => set_led(
+            params["id"].as_u64().unwrap_or(0).min(255) as u8,
+            params["value"].as_u64().unwrap_or(0).min(255) as u8,
+        ),
+        "switch_profile" => {
+            switch_profile_by_name(params["profile"].as_str().unwrap_or_default());
+        }
+        method => log_error!(
+            "Plugin `{}` called an unknown method `{}`",
+            plugin_name,
+            method
+        ),
+    }
+}
+
+/// Messages are JSON-RPC 2.0, one per line
+fn request_message(id: u64, method: &str, params: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": params,
+    })
+}
+
+/// Returns once the plugin is unloaded or has exited
+fn handle_plugin_writer(plugin_name: &str, mut stdin: ChildStdin, requests: mpsc::Receiver<Value>) {
+    for request in requests {
+        if let Err(e) = writeln!(stdin, "{}", request).and_then(|_| stdin.flush()) {
+            log_error!(
+                "Could not send a request to plugin `{}`: {}",
+                plugin_name,
+                e
+            );
+
+            break;
+        }
+    }
+}
+
+/// Kill the plugin and wait for it, so it doesn't stay as a zombie process
+fn stop_process(process: &mut Child) {
+    process.kill().ok();
+    process.wait().ok();
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .map(|extension| {
+                ["exe", "bat", "cmd"].contains(&extension.to_string_lossy().to_lowercase().as_str())
+            })
+            .unwrap_or(false)
+}
+
+fn get_plugins_path() -> PathBuf {
+    let config = CONFIG
+        .get()
+        .expect("Could not retrieve CONFIG data!")
+        .lock()
+        .unwrap();
+
+    Path::new(&config.file_path)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(PLUGINS_DIRECTORY_NAME)
+}
+
+fn get_plugins() -> &'static Mutex<Vec<Plugin>> {
+    PLUGINS.get_or_init(|| Mutex::new(vec![]))
+}
@@ -0,0 +1,67 @@
+use crate::{
+    config::{update_config_and_client, CONFIG},
+    log_error, log_info,
+    service::bus::{self, ConfigEvent, DeviceCommand, ServiceEvent},
+};
+
+/// Switch the current profile, reload the `TCP clients` and send the new profile to the device
+pub fn switch_profile(profile_index: usize) -> bool {
+    let mut config = CONFIG
+        .get()
+        .expect("Could not retrieve CONFIG data!")
+        .lock()
+        .unwrap();
+
+    if profile_index >= config.profiles.len() {
+        log_error!(
+            "Could not switch to profile `{}`, it doesn't exist!",
+            profile_index
+        );
+
+        return false;
+    }
+
+    if config.settings.current_profile == profile_index {
+        return true;
+    }
+
+    update_config_and_client(&mut config, |c| {
+        c.settings.current_profile = profile_index;
+    });
+
+    log_info!(
+        "Switched to profile `{}`",
+        config.profiles[profile_index].name
+    );
+
+    bus::send_to_device(DeviceCommand::Refresh());
+
+    bus::publish(ServiceEvent::Config(ConfigEvent::ProfileSwitched(
+        profile_index,
+        config.profiles[profile_index].name.clone(),
+    )));
+
+    true
+}
+
+/// Switch the current profile by its name or index
+pub fn switch_profile_by_name(name_or_index: &str) -> bool {
+    let profile_index = {
+        let config = CONFIG
+            .get()
+            .expect("Could not retrieve CONFIG data!")
+            .lock()
+            .unwrap();
+
+        config.find_profile(name_or_index)
+    };
+
+    match profile_index {
+        Some(index) => switch_profile(index),
+        None => {
+            log_error!("Could not find a profile named `{}`", name_or_index);
+
+            false
+        }
+    }
+}
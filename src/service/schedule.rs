@@ -0,0 +1,249 @@
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::{
+    config::{ProfileSchedule, CONFIG},
+    constants::SCHEDULE_POLL_INTERVAL,
+    log_info, log_warn,
+    service::{
+        profile::{switch_profile, switch_profile_by_name},
+        shutdown::is_shutting_down,
+    },
+};
+
+/// Profiles are only switched when the scheduled profile changes (a boundary), so a manual
+/// switch stays until the next boundary. When the schedules end, the previous profile comes back
+pub fn handle_schedule_thread() {
+    let mut last_scheduled: Option<Option<usize>> = None;
+    // Profile that was active before the schedules took over, switched back to when they end
+    let mut previous_profile: Option<String> = None;
+    let mut last_error = String::new();
+
+    loop {
+        if is_shutting_down() {
+            return;
+        }
+
+        let schedules: Vec<(usize, Vec<ProfileSchedule>)> = {
+            let config = CONFIG
+                .get()
+                .expect("Could not retrieve CONFIG data!")
+                .lock()
+                .unwrap();
+
+            config
+                .profiles
+                .iter()
+                .enumerate()
+                .filter(|(_, profile)| !profile.schedules.is_empty())
+                .map(|(index, profile)| (index, profile.schedules.clone()))
+                .collect()
+        };
+
+        let now = Local::now().naive_local();
+
+        let mut scheduled = None;
+
+        // First profile with an active schedule wins
+        for (profile_index, profile_schedules) in &schedules {
+            for schedule in profile_schedules {
+                match is_schedule_active(schedule, &now) {
+                    Ok(true) if scheduled.is_none() => scheduled = Some(*profile_index),
+                    Ok(_) => (),
+                    Err(e) => {
+                        // Only log new errors, schedules are checked repeatedly
+                        if e != last_error {
+                            log_warn!("Invalid profile schedule: {}", e);
+
+                            last_error = e;
+                        }
+                    }
+                }
+            }
+        }
+
+        // The first check is a boundary too, so a schedule that's active at startup is applied
+        if last_scheduled != Some(scheduled) {
+            let (current_index, current_name) = get_current_profile();
+
+            match scheduled {
+                Some(profile_index) => {
+                    // Back-to-back schedules keep the profile from before the first one
+                    if previous_profile.is_none() && current_index != profile_index {
+                        previous_profile = Some(current_name);
+                    }
+
+                    log_info!("Profile schedule started, switching profile...");
+
+                    switch_profile(profile_index);
+                }
+                None => {
+                    if let Some(name) = previous_profile.take() {
+                        // A profile that was switched manually during the schedule is kept
+                        if last_scheduled == Some(Some(current_index)) {
+                            log_info!("Profile schedule ended, switching back to `{}`...", name);
+
+                            switch_profile_by_name(&name);
+                        }
+                    }
+                }
+            }
+        }
+
+        last_scheduled = Some(scheduled);
+
+        std::thread::sleep(std::time::Duration::from_millis(SCHEDULE_POLL_INTERVAL));
+    }
+}
+
+fn get_current_profile() -> (usize /* index */, String /* name */) {
+    let config = CONFIG
+        .get()
+        .expect("Could not retrieve CONFIG data!")
+        .lock()
+        .unwrap();
+
+    let index = config.settings.current_profile;
+
+    (
+        index,
+        config
+            .profiles
+            .get(index)
+            .map(|profile| profile.name.clone())
+            .unwrap_or_default(),
+    )
+}
+
+/// Check the times and days of a schedule without running it
+pub fn validate_schedule(schedule: &ProfileSchedule) -> Result<(), String> {
+    is_schedule_active(schedule, &Local::now().naive_local()).map(|_| ())
+}
+
+fn is_schedule_active(schedule: &ProfileSchedule, now: &NaiveDateTime) -> Result<bool, String> {
+    let start = parse_time(&schedule.start)?;
+    let end = parse_time(&schedule.end)?;
+
+    let days = schedule
+        .days
+        .iter()
+        .map(|day| parse_days(day))
+        .collect::<Result<Vec<Vec<Weekday>>, String>>()?
+        .concat();
+
+    let is_day_included = |day: Weekday| days.is_empty() || days.contains(&day);
+
+    let time = now.time();
+    let today = now.weekday();
+
+    if start <= end {
+        return Ok(is_day_included(today) && time >= start && time < end);
+    }
+
+    // Past midnight, the range belongs to the day it started
+    Ok((is_day_included(today) && time >= start) || (is_day_included(today.pred()) && time < end))
+}
+
+pub fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| format!("`{}` isn't a valid time, expected `HH:MM`", time))
+}
+
+fn parse_days(day: &str) -> Result<Vec<Weekday>, String> {
+    match day.trim().to_lowercase().as_str() {
+        "weekdays" => Ok(vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ]),
+        "weekend" | "weekends" => Ok(vec![Weekday::Sat, Weekday::Sun]),
+        other => other
+            .parse::<Weekday>()
+            .map(|day| vec![day])
+            .map_err(|_| format!("`{}` isn't a valid day", day)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn schedule(days: &[&str], start: &str, end: &str) -> ProfileSchedule {
+        ProfileSchedule {
+            days: days.iter().map(|day| day.to_string()).collect(),
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    // 2025-02-14 is a Friday
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 2, day)
+            .unwrap()
+            .and_time(parse_time(time).unwrap())
+    }
+
+    #[test]
+    fn parses_days() {
+        assert_eq!(parse_days("Mon"), Ok(vec![Weekday::Mon]));
+        assert_eq!(parse_days(" tuesday "), Ok(vec![Weekday::Tue]));
+        assert_eq!(parse_days("weekdays").unwrap().len(), 5);
+        assert_eq!(parse_days("weekend"), Ok(vec![Weekday::Sat, Weekday::Sun]));
+        assert_eq!(parse_days("weekends"), parse_days("weekend"));
+        assert!(parse_days("someday").is_err());
+    }
+
+    #[test]
+    fn checks_daytime_schedules() {
+        let work = schedule(&["weekdays"], "09:00", "17:00");
+
+        assert_eq!(is_schedule_active(&work, &at(14, "09:00")), Ok(true));
+        assert_eq!(is_schedule_active(&work, &at(14, "16:59")), Ok(true));
+        assert_eq!(is_schedule_active(&work, &at(14, "17:00")), Ok(false));
+        assert_eq!(is_schedule_active(&work, &at(14, "08:59")), Ok(false));
+        assert_eq!(is_schedule_active(&work, &at(15, "10:00")), Ok(false));
+
+        let every_day = schedule(&[], "09:00", "17:00");
+
+        assert_eq!(is_schedule_active(&every_day, &at(15, "10:00")), Ok(true));
+    }
+
+    #[test]
+    fn checks_schedules_past_midnight() {
+        let friday_night = schedule(&["fri"], "22:00", "02:00");
+
+        assert_eq!(
+            is_schedule_active(&friday_night, &at(14, "23:00")),
+            Ok(true)
+        );
+        // Saturday morning still belongs to Friday's range
+        assert_eq!(
+            is_schedule_active(&friday_night, &at(15, "01:59")),
+            Ok(true)
+        );
+        assert_eq!(
+            is_schedule_active(&friday_night, &at(15, "02:00")),
+            Ok(false)
+        );
+        assert_eq!(
+            is_schedule_active(&friday_night, &at(15, "23:00")),
+            Ok(false)
+        );
+        // Friday morning belongs to Thursday's range, which isn't included
+        assert_eq!(
+            is_schedule_active(&friday_night, &at(14, "01:00")),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        assert!(is_schedule_active(&schedule(&[], "9am", "17:00"), &at(14, "10:00")).is_err());
+        assert!(
+            is_schedule_active(&schedule(&["fry"], "09:00", "17:00"), &at(14, "10:00")).is_err()
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    path::Path,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope};
+
+use crate::{
+    config::CONFIG,
+    constants::{
+        KEYS, SCRIPT_MAX_CALL_LEVELS, SCRIPT_MAX_COLLECTION_SIZE, SCRIPT_TIMEOUT,
+        SCRIPT_VARIABLES_FILE_NAME,
+    },
+    log_error, log_info, log_print,
+    service::{
+        interaction::{
+            get_component_value, run_component_interaction, simulate_shortcut, RunGuard,
+        },
+        profile::{switch_profile, switch_profile_by_name},
+        serial::{set_led, show_on_display},
+        template::TemplateContext,
+    },
+    utility::EnigoKey,
+};
+
+// Variables that scripts keep between runs, they're also stored in a file next to the config
+static SCRIPT_VARIABLES: OnceLock<Mutex<serde_json::Map<String, serde_json::Value>>> =
+    OnceLock::new();
+
+// Scripts are run on a separate thread, so they don't block serial
+pub fn run_script(script: String, context: TemplateContext, modkey: bool) {
+    let run_guard = RunGuard::new(&context.component_global_id);
+
+    let spawn_result = std::thread::Builder::new()
+        .name("Script".to_string())
+        .spawn(move || {
+            let _run_guard = run_guard;
+
+            let engine = create_engine();
+
+            let mut scope = Scope::new();
+
+            scope
+                .push_constant("component", context.component_global_id.clone())
+                .push_constant("profile", context.profile.clone())
+                .push_constant("modkey", modkey)
+                .push_constant("value", to_number(&context.value))
+                .push_constant("prev_value", to_number(&context.prev_value));
+
+            match engine.run_with_scope(&mut scope, &script) {
+                Ok(_) => log_info!("Script of `{}` finished.", context.component_global_id),
+                Err(e) => log_error!("Script of `{}` failed: {}", context.component_global_id, e),
+            }
+        });
+
+    if let Err(e) = spawn_result {
+        log_error!("Failed to spawn `Script` thread: {}", e);
+    }
+}
+
+/// Check the script for syntax errors without running it, used by the `Dashboard`
+pub fn validate(script: &str) -> Result<(), String> {
+    create_engine()
+        .compile(script)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Scripts can only access the device API below, no files or processes, and are stopped
+/// after `SCRIPT_TIMEOUT`
+fn create_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    let started_at = Instant::now();
+
+    engine
+        .set_max_call_levels(SCRIPT_MAX_CALL_LEVELS)
+        .set_max_string_size(SCRIPT_MAX_COLLECTION_SIZE)
+        .set_max_array_size(SCRIPT_MAX_COLLECTION_SIZE)
+        .set_max_map_size(SCRIPT_MAX_COLLECTION_SIZE)
+        .disable_symbol("eval")
+        .on_progress(move |_| {
+            if started_at.elapsed() > Duration::from_millis(SCRIPT_TIMEOUT) {
+                return Some(format!("Script took longer than {}ms", SCRIPT_TIMEOUT).into());
+            }
+
+            None
+        })
+        .on_print(|text| log_print!("[Script] {}", text))
+        .on_debug(|text, _, position| log_print!("[Script] {} ({})", text, position));
+
+    engine
+        .register_fn("run_interaction", |component: &str| {
+            run_component_interaction(component, false)
+        })
+        .register_fn("run_interaction", |component: &str, modkey: bool| {
+            run_component_interaction(component, modkey)
+        })
+        .register_fn(
+            "send_keys",
+            |keys: Array| -> Result<(), Box<EvalAltResult>> {
+                let keys = keys
+                    .into_iter()
+                    .map(|key| {
+                        let name = key.to_string();
+
+                        parse_key(&name).ok_or(format!("Unknown key `{}`", name).into())
+                    })
+                    .collect::<Result<Vec<EnigoKey>, Box<EvalAltResult>>>()?;
+
+                simulate_shortcut(&keys, "");
+
+                Ok(())
+            },
+        )
+        .register_fn("type_text", |text: &str| simulate_shortcut(&[], text))
+        .register_fn("set_led", |id: i64, value: i64| {
+            set_led(id.clamp(0, 255) as u8, value.clamp(0, 255) as u8)
+        })
+        .register_fn("display", |text: &str| show_on_display(text))
+        .register_fn("switch_profile", |name: &str| switch_profile_by_name(name))
+        .register_fn("switch_profile", |index: i64| {
+            index >= 0 && switch_profile(index as usize)
+        })
+        .register_fn("get_var", get_variable)
+        .register_fn("set_var", set_variable)
+        .register_fn("get_value", |component: &str| {
+            get_component_value(component)
+                .map(|value| to_number(&value))
+                .unwrap_or(Dynamic::UNIT)
+        })
+        .register_fn("sleep", move |duration: i64| {
+            // Sleeping can't go past the time limit
+            let remaining =
+                Duration::from_millis(SCRIPT_TIMEOUT).saturating_sub(started_at.elapsed());
+
+            std::thread::sleep(Duration::from_millis(duration.max(0) as u64).min(remaining));
+        });
+
+    engine
+}
+
+/// Key names are the same as the `Dashboard`'s. e.g. `Control`, `Shift`, `F5`, `a`
+fn parse_key(name: &str) -> Option<EnigoKey> {
+    let mut characters = name.chars();
+
+    if let (Some(letter), None) = (characters.next(), characters.next()) {
+        return Some(EnigoKey(enigo::Key::Unicode(
+            letter.to_lowercase().next().unwrap_or(letter),
+        )));
+    }
+
+    KEYS.iter()
+        .find(|key| format!("{:?}", key.0).eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+/// Component values are numbers, but they're kept as text if they can't be parsed
+fn to_number(value: &str) -> Dynamic {
+    match value.parse::<i64>() {
+        Ok(number) => Dynamic::from(number),
+        Err(_) if value.is_empty() => Dynamic::UNIT,
+        Err(_) => Dynamic::from(value.to_string()),
+    }
+}
+
+fn get_variable(name: &str) -> Dynamic {
+    get_script_variables()
+        .lock()
+        .ok()
+        .and_then(|variables| variables.get(name).cloned())
+        .and_then(|value| rhai::serde::to_dynamic(value).ok())
+        .unwrap_or(Dynamic::UNIT)
+}
+
+fn set_variable(name: &str, value: Dynamic) -> Result<(), Box<EvalAltResult>> {
+    let value: serde_json::Value = rhai::serde::from_dynamic(&value)?;
+
+    if let Ok(mut variables) = get_script_variables().lock() {
+        variables.insert(name.to_string(), value);
+
+        save_variables(&variables);
+    }
+
+    Ok(())
+}
+
+/// Flips a `true`/`false` variable and returns its new value, used by `Toggle` conditions
+pub fn toggle_variable(name: &str) -> bool {
+    let mut variables = match get_script_variables().lock() {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+
+    let value = !variables
+        .get(name)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+
+    variables.insert(name.to_string(), serde_json::Value::Bool(value));
+
+    save_variables(&variables);
+
+    value
+}
+
+fn save_variables(variables: &serde_json::Map<String, serde_json::Value>) {
+    let json = serde_json::to_string_pretty(variables).unwrap_or("{}".to_string());
+
+    if let Err(e) = std::fs::write(get_script_variables_path(), json) {
+        log_error!("Failed to save script variables: {}", e);
+    }
+}
+
+fn get_script_variables_path() -> String {
+    let config = CONFIG
+        .get()
+        .expect("Could not retrieve CONFIG data!")
+        .lock()
+        .unwrap();
+
+    let folder = Path::new(&config.file_path)
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or(".".to_string());
+
+    format!("{}/{}", folder, SCRIPT_VARIABLES_FILE_NAME)
+}
+
+fn get_script_variables() -> &'static Mutex<serde_json::Map<String, serde_json::Value>> {
+    SCRIPT_VARIABLES.get_or_init(|| {
+        let variables = std::fs::read_to_string(get_script_variables_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Mutex::new(variables)
+    })
+}
@@ -1,19 +1,26 @@
 use std::{
     error::Error,
-    sync::{Arc, Mutex, OnceLock},
+    sync::{mpsc, Arc, Mutex, OnceLock},
 };
 
 use chrono::Timelike;
 
 use crate::{
     config::{update_config_and_client, ComponentKind, CONFIG},
-    constants::{SERIAL_MESSAGE_END, SERIAL_MESSAGE_INNER_SEP, SERIAL_MESSAGE_SEP},
+    constants::{
+        SERIAL_MESSAGE_END, SERIAL_MESSAGE_INNER_SEP, SERIAL_MESSAGE_SEP, SERIAL_READ_TIMEOUT,
+    },
     log_error, log_info, log_print, log_warn,
-    service::interaction::{do_button, do_potentiometer},
-    tcp,
+    service::{
+        bus::{self, ConfigEvent, DeviceCommand, DeviceEvent, ServiceEvent},
+        interaction::release_all_held_keys,
+        shutdown::is_shutting_down,
+    },
 };
 
 pub static SERIAL: OnceLock<Mutex<Serial>> = OnceLock::new();
+static DEVICE_WRITER: OnceLock<Mutex<Serial>> = OnceLock::new(); // Shares the port with `SERIAL`
+                                                                 // while the device is connected
 
 pub struct Serial {
     port: Option<Arc<Mutex<Box<dyn serialport::SerialPort>>>>,
@@ -180,27 +187,46 @@ impl Serial {
     }
 
     pub fn handle_serial_port(&mut self) {
-        // Function to set `is_device_paired` in `SERVER_DATA`
+        // Function to let other `Service` parts know about `is_device_paired`
         let update_pairing_status = |state: bool| {
-            if let Ok(mut data) = tcp::get_server_data().lock() {
-                let mut server_data = data.clone();
-
-                server_data.is_device_paired = state;
-
-                *data = server_data;
-            }
+            bus::publish(ServiceEvent::Device(DeviceEvent::Paired(state)));
         };
         // Device and software pairing status
         let mut paired = false;
         update_pairing_status(paired);
 
         while !self.detect_device_and_connect() {
+            if is_shutting_down() {
+                return;
+            }
+
             log_warn!("Could not connect to any serial devices, retrying...");
 
             std::thread::sleep(std::time::Duration::from_millis(1000));
         }
 
-        let port = self.port.clone().unwrap();
+        // Reading has its own handle, so writing never waits for a read to time out
+        let reader = self.port.as_ref().unwrap().lock().unwrap().try_clone();
+
+        let mut reader = match reader {
+            Ok(r) => r,
+            Err(e) => {
+                log_error!("Could not open the serial port for reading: {}", e);
+
+                self.port = None;
+
+                std::thread::sleep(std::time::Duration::from_millis(1000));
+
+                return self.handle_serial_port();
+            }
+        };
+
+        reader
+            .set_timeout(std::time::Duration::from_millis(SERIAL_READ_TIMEOUT))
+            .ok();
+
+        set_device_writer_port(self.port.clone());
+
         let mut buf: Vec<u8> = vec![0; 32];
 
         let mut message = Message::new();
@@ -208,73 +234,38 @@ impl Serial {
         // Clear the input buffer to avoid bugs such as initializing the firmware twice.
         // If the app was closed before reading the message inside the buffer,
         // the message would remain in the buffer, potentially causing dual initialization.
-        port.lock()
-            .unwrap()
+        reader
             .clear(serialport::ClearBuffer::Input)
             .expect("Failed to discard input buffer");
 
         loop {
-            match port.lock().unwrap().read(buf.as_mut_slice()) {
+            if is_shutting_down() {
+                // Dropping the last references closes the port
+                set_device_writer_port(None);
+                self.port = None;
+
+                log_info!("Serial port was closed.");
+
+                return;
+            }
+
+            match reader.read(buf.as_mut_slice()) {
                 Ok(t) => message.push(std::str::from_utf8(&buf[..t]).unwrap()),
                 Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => (),
                 Err(e) => {
                     log_error!("Connection was lost: {:?}", e);
 
-                    break self.handle_serial_port();
-                }
-            }
-
-            // Handle message which `TCP client` requested to be sent via serial
-            if let Ok(mut data) = tcp::get_server_data().lock() {
-                let mut server_data = data.clone();
-
-                if !server_data.pending_serial_message.is_empty() {
-                    if server_data.pending_serial_message == "refresh_device" {
-                        let mut config = CONFIG
-                            .get()
-                            .expect("Could not retrieve CONFIG data!")
-                            .lock()
-                            .unwrap();
+                    set_device_writer_port(None);
 
-                        self.refresh_device(&mut config);
-                    } else {
-                        let mut is_uploading = false;
-                        let mut is_saving_to_flash = false;
+                    // Keys could stay pressed forever if the device was disconnected mid-hold
+                    release_all_held_keys();
 
-                        // Ask device to re-send its internal data if some thing was uploaded
-                        if server_data.pending_serial_message.starts_with('u') {
-                            is_uploading = true;
-
-                            if server_data.pending_serial_message.chars().nth(1) == Some('M') {
-                                is_saving_to_flash = true;
-                            }
-
-                            // Remove the char because device's messaging structure is different
-                            server_data.pending_serial_message.remove(1);
-                        }
-
-                        self.write(server_data.pending_serial_message);
-
-                        if is_uploading {
-                            // `i` => Internal, `data` => device's data
-                            self.write("idata".to_string());
-
-                            // Save to device's flash memory
-                            if is_saving_to_flash {
-                                self.write("m1".to_string()); // `m` => Memory, `1` => True
-                            }
-                        }
-                    }
-
-                    server_data.pending_serial_message = String::new();
-
-                    *data = server_data;
+                    break self.handle_serial_port();
                 }
             }
 
-            let (ready, key, value) = message.parse();
-
-            if ready {
+            // A single read can contain more than one message
+            while let (true, key, value) = message.parse() {
                 let mut valid = false;
                 let mut component = ComponentKind::None;
                 let mut id = 0;
@@ -284,6 +275,9 @@ impl Serial {
                     "READY" => {
                         log_print!("[INCOMING] key: {} | value: {}", key, value);
 
+                        // Device was restarted, so no buttons are being held anymore
+                        release_all_held_keys();
+
                         self.write("c1".to_string()); // `c` => Connection, `1` => true
                     }
                     "PAIRED" => {
@@ -306,35 +300,17 @@ impl Serial {
 
                         match data_key {
                             // 'v' => *Firmeware* Version
-                            'v' => {
-                                if let Ok(mut data) = tcp::get_server_data().lock() {
-                                    let mut server_data = data.clone();
-
-                                    server_data.firmware_version = data_value.to_string();
-
-                                    *data = server_data;
-                                }
-                            }
+                            'v' => bus::publish(ServiceEvent::Device(
+                                DeviceEvent::FirmwareVersion(data_value.to_string()),
+                            )),
                             // 'b' => Buttons
-                            'b' => {
-                                if let Ok(mut data) = tcp::get_server_data().lock() {
-                                    let mut server_data = data.clone();
-
-                                    server_data.raw_layout.0 = data_value.to_string();
-
-                                    *data = server_data;
-                                }
-                            }
+                            'b' => bus::publish(ServiceEvent::Device(DeviceEvent::RawButtons(
+                                data_value.to_string(),
+                            ))),
                             // 'p' => Potentiometers
-                            'p' => {
-                                if let Ok(mut data) = tcp::get_server_data().lock() {
-                                    let mut server_data = data.clone();
-
-                                    server_data.raw_layout.1 = data_value.to_string();
-
-                                    *data = server_data;
-                                }
-                            }
+                            'p' => bus::publish(ServiceEvent::Device(
+                                DeviceEvent::RawPotentiometers(data_value.to_string()),
+                            )),
                             _ => {}
                         }
                     }
@@ -366,6 +342,15 @@ impl Serial {
                                 update_config_and_client(&mut config, |c| {
                                     c.settings.current_profile = selected_profile;
                                 });
+
+                                if let Some(profile) = config.profiles.get(selected_profile) {
+                                    bus::publish(ServiceEvent::Config(
+                                        ConfigEvent::ProfileSwitched(
+                                            selected_profile,
+                                            profile.name.clone(),
+                                        ),
+                                    ));
+                                }
                             }
                             _ => (),
                         }
@@ -377,7 +362,8 @@ impl Serial {
                             'p' => ComponentKind::Potentiometer,
                             _ => ComponentKind::None,
                         };
-                        id = key[2..].trim().parse::<u8>().unwrap_or(0);
+                        // Keys that are too short, e.g. a message without a key, get the id zero
+                        id = key.get(2..).unwrap_or("").trim().parse::<u8>().unwrap_or(0);
 
                         match key.chars().nth(1).unwrap_or('\0') {
                             'm' => modkey = false,
@@ -404,18 +390,41 @@ impl Serial {
                     value
                 );
 
-                match component {
-                    ComponentKind::Button => {
-                        do_button(id, value.parse::<i8>().unwrap_or(0), modkey, self)
-                    }
-                    ComponentKind::Potentiometer => {
-                        do_potentiometer(id, value.parse::<u8>().unwrap_or(0))
-                    }
-                    _ => (),
-                }
+                // Interactions run on their own thread, so reading isn't blocked
+                bus::publish(ServiceEvent::Device(DeviceEvent::Input(
+                    component, id, value, modkey,
+                )));
             }
+        }
+    }
+
+    /// Write a message that was requested by the `TCP client` or sent by the `Service`
+    fn write_outgoing_message(&mut self, mut outgoing_message: String) {
+        let mut is_uploading = false;
+        let mut is_saving_to_flash = false;
+
+        // Ask device to re-send its internal data if some thing was uploaded
+        if outgoing_message.starts_with('u') {
+            is_uploading = true;
 
-            std::thread::sleep(std::time::Duration::from_millis(10));
+            if outgoing_message.chars().nth(1) == Some('M') {
+                is_saving_to_flash = true;
+            }
+
+            // Remove the char because device's messaging structure is different
+            outgoing_message.remove(1);
+        }
+
+        self.write(outgoing_message);
+
+        if is_uploading {
+            // `i` => Internal, `data` => device's data
+            self.write("idata".to_string());
+
+            // Save to device's flash memory
+            if is_saving_to_flash {
+                self.write("m1".to_string()); // `m` => Memory, `1` => True
+            }
         }
     }
 
@@ -472,28 +481,141 @@ impl Message {
         let mut key = String::new();
         let mut value = String::new();
 
-        if !self.0.is_empty() && self.0.contains(SERIAL_MESSAGE_END) && !ready {
+        // Everything up to the first SERIAL_MESSAGE_END is consumed, even without a key,
+        // e.g. when the port was opened mid-message, so the same part is never parsed twice
+        if let Some((message, next_message)) = self.0.split_once(SERIAL_MESSAGE_END) {
             ready = true;
 
             // Separate the key from the value by SERIAL_MESSAGE_SEP
-            if let Some((k, raw_v)) = self.0.split_once(SERIAL_MESSAGE_SEP) {
+            if let Some((k, v)) = message.split_once(SERIAL_MESSAGE_SEP) {
                 key = k.to_string();
+                value = v.to_string();
+            }
 
-                // Remove the SERIAL_MESSAGE_END and anything after that
-                if let Some((v, next_message)) = raw_v.split_once(SERIAL_MESSAGE_END) {
-                    value = v.to_string();
+            self.0 = next_message.to_string();
+        }
 
-                    self.0 = next_message.to_string();
-                }
+        (ready, key, value)
+    }
+}
+
+/// Show a short status text on the device's display
+pub fn show_on_display(text: &str) {
+    // Device messages end with a new line, so the text must stay on a single line
+    let text = text.replace(['\r', '\n'], " ");
+
+    // `s` => Status text
+    bus::send_to_device(DeviceCommand::Message(format!("s{}", text.trim())));
+}
+
+/// Set the state of an LED on the device, `value` is its brightness (0 turns it off)
+pub fn set_led(id: u8, value: u8) {
+    // `l` => LED
+    bus::send_to_device(DeviceCommand::Message(format!(
+        "l{}{}{}",
+        id, SERIAL_MESSAGE_INNER_SEP, value
+    )));
+}
+
+/// Write `DeviceCommand`s from other `Service` parts as soon as they arrive
+pub fn handle_serial_writer_thread(commands: mpsc::Receiver<DeviceCommand>) {
+    for command in commands {
+        let mut writer = match get_device_writer().lock() {
+            Ok(w) => w,
+            Err(_) => continue,
+        };
+
+        match command {
+            DeviceCommand::Message(message) => writer.write_outgoing_message(message),
+            DeviceCommand::Refresh() => {
+                let mut config = CONFIG
+                    .get()
+                    .expect("Could not retrieve CONFIG data!")
+                    .lock()
+                    .unwrap();
+
+                writer.refresh_device(&mut config);
             }
         }
+    }
+}
 
-        (ready, key, value)
+fn set_device_writer_port(port: Option<Arc<Mutex<Box<dyn serialport::SerialPort>>>>) {
+    if let Ok(mut writer) = get_device_writer().lock() {
+        writer.port = port;
     }
 }
 
+fn get_device_writer() -> &'static Mutex<Serial> {
+    DEVICE_WRITER.get_or_init(|| Mutex::new(Serial::default()))
+}
+
 pub fn init() {
     let serial = Serial::default();
 
     SERIAL.get_or_init(|| Mutex::new(serial));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(buffer: &str) -> (Vec<(String, String)>, String) {
+        let mut message = Message::new();
+        let mut messages = vec![];
+
+        message.push(buffer);
+
+        while let (true, key, value) = message.parse() {
+            messages.push((key, value));
+        }
+
+        (messages, message.0)
+    }
+
+    #[test]
+    fn parses_messages() {
+        assert_eq!(
+            parse_all("READY:1;b1:1;pm"),
+            (
+                vec![
+                    ("READY".to_string(), "1".to_string()),
+                    ("b1".to_string(), "1".to_string())
+                ],
+                "pm".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn skips_messages_without_a_key() {
+        assert_eq!(
+            parse_all("5;pm1:"),
+            (vec![(String::new(), String::new())], "pm1:".to_string())
+        );
+        assert_eq!(
+            parse_all("ab;cd:1;"),
+            (
+                vec![
+                    (String::new(), String::new()),
+                    ("cd".to_string(), "1".to_string())
+                ],
+                String::new()
+            )
+        );
+    }
+
+    #[test]
+    fn waits_for_the_rest_of_a_message() {
+        let mut message = Message::new();
+
+        message.push("PAIR");
+        assert!(!message.parse().0);
+
+        message.push("ED:1;");
+        assert_eq!(
+            message.parse(),
+            (true, "PAIRED".to_string(), "1".to_string())
+        );
+    }
+}
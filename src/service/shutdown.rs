@@ -0,0 +1,107 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Condvar, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::CONFIG,
+    constants::SHUTDOWN_TIMEOUT,
+    log::flush_logs,
+    log_info, log_warn,
+    service::{bus, interaction::release_all_held_keys, mqtt, plugin::unload_plugins},
+    tcp,
+    utility::restart,
+};
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN_REQUEST: (Mutex<Option<bool /* should_restart */>>, Condvar) =
+    (Mutex::new(None), Condvar::new());
+
+/// Threads that poll something check this and stop doing work
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::Relaxed)
+}
+
+/// Ask the main thread to stop the `Service`, then exit or start it again (`should_restart`)
+pub fn shutdown(should_restart: bool) {
+    // Only the first caller shuts down, e.g. `Quit` was clicked while restarting
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    log_info!(
+        "{} the app...",
+        if should_restart {
+            "Restarting"
+        } else {
+            "Closing"
+        }
+    );
+
+    let (request, signal) = &SHUTDOWN_REQUEST;
+
+    if let Ok(mut request) = request.lock() {
+        *request = Some(should_restart);
+    }
+
+    signal.notify_all();
+}
+
+/// Blocks the main thread until `shutdown` is called, then stops every thread in `threads`
+pub fn handle_shutdown(threads: Vec<JoinHandle<()>>) {
+    let should_restart = {
+        let (request, signal) = &SHUTDOWN_REQUEST;
+
+        let request = signal
+            .wait_while(request.lock().unwrap(), |request| request.is_none())
+            .unwrap();
+
+        request.unwrap_or(false)
+    };
+
+    // Threads that wait for events or connections see the flag or a closed channel
+    bus::close();
+    tcp::close_subscribers();
+    mqtt::disconnect();
+
+    let started_at = Instant::now();
+
+    for thread in threads {
+        let name = thread.thread().name().unwrap_or("Unnamed").to_string();
+
+        while !thread.is_finished() {
+            if started_at.elapsed() > Duration::from_millis(SHUTDOWN_TIMEOUT) {
+                break;
+            }
+
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        if thread.is_finished() {
+            thread.join().ok();
+        } else {
+            log_warn!("`{}` thread didn't stop in time!", name);
+        }
+    }
+
+    release_all_held_keys();
+
+    unload_plugins();
+
+    // Waits for a config write that's in progress and keeps new ones from starting
+    let _config = CONFIG.get().map(|config| config.lock());
+
+    log_info!("Shutdown is complete.");
+
+    flush_logs();
+
+    if should_restart {
+        restart();
+    }
+
+    std::process::exit(0);
+}
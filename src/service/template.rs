@@ -0,0 +1,677 @@
+use chrono::format::{Item, StrftimeItems};
+
+/// Values that can be used in interaction templates. e.g. `{value}`, `{profile}`
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    pub component_global_id: String, // e.g. `Potentiometer:1`
+    pub profile: String,
+    pub value: String,
+    pub prev_value: String, // Empty if the component hasn't been used before
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(Placeholder),
+}
+
+enum Placeholder {
+    Id,
+    Component,
+    Profile,
+    Date(String /* format */),
+    Env(String /* name */),
+    Number(Expression, Vec<Filter>),
+}
+
+#[derive(Clone, Copy)]
+enum Variable {
+    Value,
+    PrevValue,
+    Delta,
+    Percent,
+}
+
+enum Expression {
+    Number(f64),
+    Variable(Variable),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, char /* operator */, Box<Expression>),
+}
+
+enum Filter {
+    Round(u32 /* decimals */),
+    Floor,
+    Ceil,
+    Abs,
+    Int,
+    Map(
+        f64, /* in_min */
+        f64, /* in_max */
+        f64, /* out_min */
+        f64, /* out_max */
+    ),
+    Clamp(f64 /* min */, f64 /* max */),
+}
+
+const OPERATORS: [char; 5] = ['+', '-', '*', '/', '%'];
+const NAMES: [&str; 9] = [
+    "value",
+    "prev_value",
+    "delta",
+    "percent",
+    "id",
+    "component",
+    "profile",
+    "date",
+    "env",
+];
+
+impl TemplateContext {
+    fn number(&self, variable: Variable) -> Result<f64, String> {
+        let value = parse_number("value", &self.value)?;
+        let prev_value = if self.prev_value.is_empty() {
+            value
+        } else {
+            parse_number("prev_value", &self.prev_value)?
+        };
+
+        Ok(match variable {
+            Variable::Value => value,
+            Variable::PrevValue => prev_value,
+            Variable::Delta => value - prev_value,
+            Variable::Percent => value * 100.0 / self.max_value(),
+        })
+    }
+
+    /// Potentiometers are mapped between 0-99 in the device, buttons are either 0 or 1
+    fn max_value(&self) -> f64 {
+        if self.component_global_id.starts_with("Potentiometer") {
+            99.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Replace the placeholders of the template. e.g. `{value*2.55|round}`
+pub fn render(template: &str, context: &TemplateContext) -> Result<String, String> {
+    let mut result = String::new();
+
+    for segment in parse(template)? {
+        match segment {
+            Segment::Text(text) => result.push_str(text),
+            Segment::Placeholder(placeholder) => {
+                result.push_str(&render_placeholder(&placeholder, context)?)
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Check the template for errors without rendering it, used by the `Dashboard`
+pub fn validate(template: &str) -> Result<(), String> {
+    parse(template).map(|_| ())
+}
+
+/// Braces that don't start with a known variable (JSON, shell `${VAR}`, `find -exec {}`, etc.)
+/// are kept as they are, so are double braces (Go or Jinja templates). `{{value}` is `{value}`
+fn parse(template: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = vec![];
+    let mut text_start = 0;
+    let mut search_start = 0;
+
+    while let Some(offset) = template[search_start..].find('{') {
+        let start = search_start + offset;
+
+        search_start = start + 1;
+
+        if template[start + 1..].starts_with('{') {
+            search_start = start + 2;
+
+            // Only an escaped placeholder loses a brace, e.g. `{{.Names}}` or `{{value}}` don't
+            let content = &template[start + 2..];
+            let is_escaped = match content.find(['{', '}']) {
+                Some(o) => {
+                    content[o..].starts_with('}')
+                        && !content[o + 1..].starts_with('}')
+                        && is_placeholder(&content[..o])
+                }
+                None => false,
+            };
+
+            if is_escaped {
+                segments.push(Segment::Text(&template[text_start..start + 1]));
+
+                text_start = start + 2;
+            }
+
+            continue;
+        }
+
+        if template[..start].ends_with('$') {
+            continue;
+        }
+
+        let end = match template[start + 1..].find(['{', '}']) {
+            Some(o) if template[start + 1 + o..].starts_with('}') => start + 1 + o,
+            _ => continue,
+        };
+
+        let content = &template[start + 1..end];
+
+        if !is_placeholder(content) {
+            continue;
+        }
+
+        let placeholder =
+            parse_placeholder(content).map_err(|e| format!("`{{{}}}`: {}", content, e))?;
+
+        if text_start < start {
+            segments.push(Segment::Text(&template[text_start..start]));
+        }
+
+        segments.push(Segment::Placeholder(placeholder));
+
+        text_start = end + 1;
+        search_start = end + 1;
+    }
+
+    if text_start < template.len() {
+        segments.push(Segment::Text(&template[text_start..]));
+    }
+
+    Ok(segments)
+}
+
+/// Whether the braces' content starts with a known name. e.g. `value`, `(value + 1) * 2`, `date:%F`
+fn is_placeholder(content: &str) -> bool {
+    let content = content.trim_start_matches(|c: char| c == '(' || c == '-' || c.is_whitespace());
+
+    let name_length = identifier_length(content);
+
+    if !NAMES.contains(&&content[..name_length]) {
+        return false;
+    }
+
+    match content[name_length..].trim_start().chars().next() {
+        None => true,
+        Some(c) => c == ':' || c == '|' || c == ')' || OPERATORS.contains(&c),
+    }
+}
+
+fn parse_placeholder(content: &str) -> Result<Placeholder, String> {
+    let content = content.trim();
+
+    let (name, rest) = content.split_at(identifier_length(content));
+    let argument = rest.trim_start().strip_prefix(':');
+
+    match (name, argument) {
+        ("date", _) if rest.is_empty() || argument.is_some() => {
+            let format = argument.unwrap_or("%F %T").to_string();
+
+            if StrftimeItems::new(&format).any(|item| item == Item::Error) {
+                return Err(format!("Invalid date format `{}`", format));
+            }
+
+            return Ok(Placeholder::Date(format));
+        }
+        ("env", Some(variable)) if !variable.trim().is_empty() => {
+            return Ok(Placeholder::Env(variable.trim().to_string()));
+        }
+        ("env", _) => return Err("Missing the variable name. e.g. `{env:HOME}`".to_string()),
+        ("id", _) if rest.is_empty() => return Ok(Placeholder::Id),
+        ("component", _) if rest.is_empty() => return Ok(Placeholder::Component),
+        ("profile", _) if rest.is_empty() => return Ok(Placeholder::Profile),
+        ("id" | "component" | "profile", _) => {
+            return Err(format!("`{}` is a text and can't be modified", name));
+        }
+        (_, Some(_)) => return Err(format!("Unexpected `:` after `{}`", name)),
+        _ => (),
+    }
+
+    let mut parts = content.split('|');
+
+    let expression = parse_expression(parts.next().unwrap_or_default())?;
+    let filters = parts
+        .map(parse_filter)
+        .collect::<Result<Vec<Filter>, String>>()?;
+
+    Ok(Placeholder::Number(expression, filters))
+}
+
+fn parse_expression(text: &str) -> Result<Expression, String> {
+    let tokens = tokenize(text)?;
+    let mut position = 0;
+
+    let expression = parse_sum(&tokens, &mut position)?;
+
+    if position < tokens.len() {
+        return Err(format!("Unexpected `{}`", tokens[position]));
+    }
+
+    Ok(expression)
+}
+
+fn tokenize(text: &str) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+
+            tokens.push(number);
+        } else if c.is_ascii_lowercase() || c == '_' {
+            let mut name = String::new();
+
+            while let Some(&c) = chars
+                .peek()
+                .filter(|c| c.is_ascii_lowercase() || **c == '_')
+            {
+                name.push(c);
+                chars.next();
+            }
+
+            tokens.push(name);
+        } else if OPERATORS.contains(&c) || c == '(' || c == ')' {
+            tokens.push(c.to_string());
+            chars.next();
+        } else {
+            return Err(format!("Unexpected `{}`", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+// sum := product (('+' | '-') product)*
+fn parse_sum(tokens: &[String], position: &mut usize) -> Result<Expression, String> {
+    let mut expression = parse_product(tokens, position)?;
+
+    while let Some(operator @ ("+" | "-")) = tokens.get(*position).map(|t| t.as_str()) {
+        *position += 1;
+
+        let right = parse_product(tokens, position)?;
+
+        expression = Expression::Binary(
+            Box::new(expression),
+            operator.chars().next().unwrap_or('+'),
+            Box::new(right),
+        );
+    }
+
+    Ok(expression)
+}
+
+// product := factor (('*' | '/' | '%') factor)*
+fn parse_product(tokens: &[String], position: &mut usize) -> Result<Expression, String> {
+    let mut expression = parse_factor(tokens, position)?;
+
+    while let Some(operator @ ("*" | "/" | "%")) = tokens.get(*position).map(|t| t.as_str()) {
+        *position += 1;
+
+        let right = parse_factor(tokens, position)?;
+
+        if operator != "*" && matches!(right, Expression::Number(n) if n == 0.0) {
+            return Err("Division by zero".to_string());
+        }
+
+        expression = Expression::Binary(
+            Box::new(expression),
+            operator.chars().next().unwrap_or('*'),
+            Box::new(right),
+        );
+    }
+
+    Ok(expression)
+}
+
+// factor := number | variable | '-' factor | '(' sum ')'
+fn parse_factor(tokens: &[String], position: &mut usize) -> Result<Expression, String> {
+    let token = match tokens.get(*position) {
+        Some(t) => t.as_str(),
+        None => return Err("Unexpected end of the expression".to_string()),
+    };
+
+    *position += 1;
+
+    match token {
+        "-" => Ok(Expression::Negate(Box::new(parse_factor(
+            tokens, position,
+        )?))),
+        "(" => {
+            let expression = parse_sum(tokens, position)?;
+
+            if tokens.get(*position).map(|t| t.as_str()) != Some(")") {
+                return Err("Missing `)`".to_string());
+            }
+
+            *position += 1;
+
+            Ok(expression)
+        }
+        "value" => Ok(Expression::Variable(Variable::Value)),
+        "prev_value" => Ok(Expression::Variable(Variable::PrevValue)),
+        "delta" => Ok(Expression::Variable(Variable::Delta)),
+        "percent" => Ok(Expression::Variable(Variable::Percent)),
+        "id" | "component" | "profile" | "date" | "env" => {
+            Err(format!("`{}` can't be used in calculations", token))
+        }
+        _ if token.starts_with(|c: char| c.is_ascii_digit() || c == '.') => token
+            .parse::<f64>()
+            .map(Expression::Number)
+            .map_err(|_| format!("Invalid number `{}`", token)),
+        _ => Err(format!("Unknown variable `{}`", token)),
+    }
+}
+
+fn parse_filter(text: &str) -> Result<Filter, String> {
+    let (name, arguments) = match text.split_once(':') {
+        Some((name, arguments)) => (name.trim(), arguments.split(',').collect::<Vec<&str>>()),
+        None => (text.trim(), vec![]),
+    };
+
+    let arguments = arguments
+        .iter()
+        .map(|argument| {
+            argument
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid number `{}` in `{}`", argument.trim(), name))
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+
+    match (name, arguments.as_slice()) {
+        ("round", []) => Ok(Filter::Round(0)),
+        ("round", [decimals]) if *decimals >= 0.0 => Ok(Filter::Round(*decimals as u32)),
+        ("floor", []) => Ok(Filter::Floor),
+        ("ceil", []) => Ok(Filter::Ceil),
+        ("abs", []) => Ok(Filter::Abs),
+        ("int", []) => Ok(Filter::Int),
+        ("map", [in_min, in_max, _, _]) if in_min == in_max => {
+            Err("`map` input range can't be empty".to_string())
+        }
+        ("map", [in_min, in_max, out_min, out_max]) => {
+            Ok(Filter::Map(*in_min, *in_max, *out_min, *out_max))
+        }
+        ("clamp", [min, max]) if min <= max => Ok(Filter::Clamp(*min, *max)),
+        ("round", _) => Err("Usage: `round` or `round:decimals`".to_string()),
+        ("map", _) => Err("Usage: `map:in_min,in_max,out_min,out_max`".to_string()),
+        ("clamp", _) => Err("Usage: `clamp:min,max`".to_string()),
+        ("floor" | "ceil" | "abs" | "int", _) => Err(format!("`{}` has no arguments", name)),
+        _ => Err(format!("Unknown filter `{}`", name)),
+    }
+}
+
+fn render_placeholder(
+    placeholder: &Placeholder,
+    context: &TemplateContext,
+) -> Result<String, String> {
+    Ok(match placeholder {
+        Placeholder::Id => context
+            .component_global_id
+            .split_once(':')
+            .map(|(_, id)| id.to_string())
+            .unwrap_or_default(),
+        Placeholder::Component => context
+            .component_global_id
+            .split_once(':')
+            .map(|(kind, _)| kind.to_string())
+            .unwrap_or_default(),
+        Placeholder::Profile => context.profile.clone(),
+        Placeholder::Date(format) => chrono::Local::now().format(format).to_string(),
+        Placeholder::Env(name) => std::env::var(name).unwrap_or_default(),
+        Placeholder::Number(expression, filters) => {
+            let mut number = evaluate(expression, context)?;
+
+            for filter in filters {
+                number = apply_filter(filter, number);
+            }
+
+            format_number(number)?
+        }
+    })
+}
+
+fn evaluate(expression: &Expression, context: &TemplateContext) -> Result<f64, String> {
+    Ok(match expression {
+        Expression::Number(number) => *number,
+        Expression::Variable(variable) => context.number(*variable)?,
+        Expression::Negate(expression) => -evaluate(expression, context)?,
+        Expression::Binary(left, operator, right) => {
+            let (left, right) = (evaluate(left, context)?, evaluate(right, context)?);
+
+            match operator {
+                '+' => left + right,
+                '-' => left - right,
+                '*' => left * right,
+                '/' if right == 0.0 => return Err("Division by zero".to_string()),
+                '/' => left / right,
+                '%' if right == 0.0 => return Err("Division by zero".to_string()),
+                _ => left % right,
+            }
+        }
+    })
+}
+
+fn apply_filter(filter: &Filter, number: f64) -> f64 {
+    match filter {
+        Filter::Round(decimals) => {
+            let factor = 10_f64.powi(*decimals as i32);
+
+            (number * factor).round() / factor
+        }
+        Filter::Floor => number.floor(),
+        Filter::Ceil => number.ceil(),
+        Filter::Abs => number.abs(),
+        Filter::Int => number.trunc(),
+        Filter::Map(in_min, in_max, out_min, out_max) => {
+            out_min + (number - in_min) * (out_max - out_min) / (in_max - in_min)
+        }
+        Filter::Clamp(min, max) => number.clamp(*min, *max),
+    }
+}
+
+/// Numbers are rounded to 6 decimals to hide floating point errors. e.g. `127.49999999999999`
+fn format_number(number: f64) -> Result<String, String> {
+    if !number.is_finite() {
+        return Err(format!("Invalid result `{}`", number));
+    }
+
+    let number = (number * 1_000_000.0).round() / 1_000_000.0;
+
+    // Avoid `-0`
+    Ok(format!("{}", if number == 0.0 { 0.0 } else { number }))
+}
+
+/// Length of the variable/filter name at the start of the text
+fn identifier_length(text: &str) -> usize {
+    text.find(|c: char| !(c.is_ascii_lowercase() || c == '_'))
+        .unwrap_or(text.len())
+}
+
+fn parse_number(name: &str, text: &str) -> Result<f64, String> {
+    text.trim()
+        .parse::<f64>()
+        .map_err(|_| format!("`{}` isn't a number", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(component_global_id: &str, value: &str, prev_value: &str) -> TemplateContext {
+        TemplateContext {
+            component_global_id: component_global_id.to_string(),
+            profile: "Work".to_string(),
+            value: value.to_string(),
+            prev_value: prev_value.to_string(),
+        }
+    }
+
+    fn render_potentiometer(template: &str) -> Result<String, String> {
+        render(template, &context("Potentiometer:2", "40", "30"))
+    }
+
+    #[test]
+    fn renders_text_placeholders() {
+        assert_eq!(
+            render_potentiometer("{component} {id} on {profile}").unwrap(),
+            "Potentiometer 2 on Work"
+        );
+        assert_eq!(
+            render_potentiometer("{ value }, {prev_value}, {delta}").unwrap(),
+            "40, 30, 10"
+        );
+    }
+
+    #[test]
+    fn keeps_other_braces() {
+        for template in [
+            "{foo}",
+            "{foo * 2}",
+            "{\"value\": 1}",
+            "echo ${HOME}",
+            r"find . -exec rm {} \;",
+            "{(a|b)}",
+            "{2}",
+            "{ value",
+        ] {
+            assert_eq!(render_potentiometer(template).unwrap(), template);
+        }
+
+        assert_eq!(
+            render_potentiometer("{\"volume\": {value}}").unwrap(),
+            "{\"volume\": 40}"
+        );
+    }
+
+    #[test]
+    fn escapes_braces() {
+        assert_eq!(render_potentiometer("{{value}").unwrap(), "{value}");
+        assert_eq!(
+            render_potentiometer("{{value} = {value}").unwrap(),
+            "{value} = 40"
+        );
+        assert!(validate("{{value|unknown}").is_ok());
+    }
+
+    #[test]
+    fn keeps_double_braces() {
+        for template in [
+            "docker ps --format '{{.Names}}'",
+            "{{foo}}",
+            "{{ value }}",
+            "{{value|unknown}}",
+            "Hello {{ name }}!",
+            "{{",
+        ] {
+            assert_eq!(render_potentiometer(template).unwrap(), template);
+        }
+    }
+
+    #[test]
+    fn calculates() {
+        assert_eq!(render_potentiometer("{value * 2 + 1}").unwrap(), "81");
+        assert_eq!(render_potentiometer("{(value + 1) * 2}").unwrap(), "82");
+        assert_eq!(render_potentiometer("{-value + 50}").unwrap(), "10");
+        assert_eq!(render_potentiometer("{value % 7}").unwrap(), "5");
+        assert_eq!(
+            render_potentiometer("{value / 3|round:2}").unwrap(),
+            "13.33"
+        );
+        assert_eq!(render_potentiometer("{value * 2.55}").unwrap(), "102");
+        assert_eq!(render_potentiometer("{delta - value}").unwrap(), "-30");
+    }
+
+    #[test]
+    fn calculates_percent() {
+        assert_eq!(render_potentiometer("{percent|round}").unwrap(), "40");
+        assert_eq!(
+            render("{percent}", &context("Button:1", "1", "")).unwrap(),
+            "100"
+        );
+        // Without a previous value, it's the same as the current one
+        assert_eq!(
+            render("{delta}", &context("Button:1", "1", "")).unwrap(),
+            "0"
+        );
+    }
+
+    #[test]
+    fn applies_filters() {
+        assert_eq!(render_potentiometer("{value / 3|floor}").unwrap(), "13");
+        assert_eq!(render_potentiometer("{value / 3|ceil}").unwrap(), "14");
+        assert_eq!(render_potentiometer("{-value / 3|int}").unwrap(), "-13");
+        assert_eq!(render_potentiometer("{-value|abs}").unwrap(), "40");
+        assert_eq!(render_potentiometer("{value|clamp:0,10}").unwrap(), "10");
+        assert_eq!(
+            render_potentiometer("{value|map:0,80,-10,10}").unwrap(),
+            "0"
+        );
+        assert_eq!(
+            render_potentiometer("{value|map:0,80,0,1|round:1|clamp:0,0.4}").unwrap(),
+            "0.4"
+        );
+    }
+
+    #[test]
+    fn renders_dates_and_environment() {
+        assert_eq!(
+            render_potentiometer("{date:%Y}").unwrap(),
+            chrono::Local::now().format("%Y").to_string()
+        );
+        assert_eq!(
+            render_potentiometer("{env:PADPAD_TEMPLATE_TEST_MISSING}").unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_placeholders() {
+        for template in [
+            "{value / 0}",
+            "{value % 0}",
+            "{value +}",
+            "{(value + 1}",
+            "{value|unknown}",
+            "{value|round:-1}",
+            "{value|clamp:10,0}",
+            "{value|map:1,1,0,10}",
+            "{value|abs:2}",
+            "{id * 2}",
+            "{profile|round}",
+            "{value * profile}",
+            "{value * foo}",
+            "{value:2}",
+            "{env}",
+            "{date:%Q}",
+        ] {
+            assert!(
+                validate(template).is_err(),
+                "`{}` should be invalid",
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert_eq!(
+            render("{value * 2}", &context("Potentiometer:2", "loud", "")).unwrap_err(),
+            "`value` isn't a number"
+        );
+        assert_eq!(
+            render("{value / delta}", &context("Button:1", "1", "1")).unwrap_err(),
+            "Division by zero"
+        );
+    }
+}
@@ -3,28 +3,30 @@ use tray_item::{IconSource, TrayItem};
 use crate::{
     config::{update_config_and_client, CONFIG},
     log_error, log_info,
-    utility::{get_app_directory, restart},
+    service::{focus, shutdown::shutdown},
+    utility::get_app_directory,
 };
 
+pub fn open_dashboard() {
+    log_info!("Opening dashboard...");
+
+    if let Ok(app_dir) = get_app_directory() {
+        let dashboard_path = std::path::Path::new(&app_dir).join("dashboard");
+
+        let result = std::process::Command::new(&dashboard_path).spawn();
+        if let Err(e) = result {
+            log_error!("{}", e);
+        }
+    }
+}
+
 pub fn handle_tray_thread() {
     #[cfg(target_os = "linux")]
     gtk::init().unwrap();
 
     let mut tray = TrayItem::new("PadPad", IconSource::Resource("app-icon")).unwrap();
 
-    tray.add_menu_item("Dashboard", || {
-        log_info!("Opening dashboard...");
-
-        if let Ok(app_dir) = get_app_directory() {
-            let dashboard_path = std::path::Path::new(&app_dir).join("dashboard");
-
-            let result = std::process::Command::new(&dashboard_path).spawn();
-            if let Err(e) = result {
-                log_error!("{}", e);
-            }
-        }
-    })
-    .unwrap();
+    tray.add_menu_item("Dashboard", open_dashboard).unwrap();
 
     tray.inner_mut().add_separator().unwrap();
 
@@ -44,21 +46,17 @@ pub fn handle_tray_thread() {
     })
     .unwrap();
 
-    tray.add_menu_item("Restart app", || {
-        log_info!("Restarting the app...");
-
-        restart()
+    tray.add_menu_item("Pause/Resume auto profiles", || {
+        focus::set_paused(!focus::is_paused());
     })
     .unwrap();
 
-    tray.inner_mut().add_separator().unwrap();
+    tray.add_menu_item("Restart app", || shutdown(true))
+        .unwrap();
 
-    tray.add_menu_item("Quit", || {
-        log_info!("Closing the app...");
+    tray.inner_mut().add_separator().unwrap();
 
-        std::process::exit(0);
-    })
-    .unwrap();
+    tray.add_menu_item("Quit", || shutdown(false)).unwrap();
 
     #[cfg(target_os = "linux")]
     gtk::main();
@@ -1,130 +1,478 @@
 use std::{
-    io::{ErrorKind, Read, Write},
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
     net::{TcpListener, TcpStream},
-    sync::{Arc, Mutex, OnceLock},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
 };
 
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
 use crate::{
-    config::CONFIG,
+    config::{check_config, update_config_and_client, ComponentKind, Config, CONFIG},
     constants::{
-        DEBUG_TCP_CLIENT_CONNECTION, DEBUG_TCP_SERVER_MESSAGE_CONFIRMATION, SERIAL_MESSAGE_SEP,
-        TCP_BUFFER_SIZE, TCP_READ_TIMEOUT, TCP_SERVER_ADDR,
+        APP_VERSION, DEBUG_TCP_CLIENT_CONNECTION, DEBUG_TCP_SERVER_MESSAGE_CONFIRMATION,
+        IPC_AUTHENTICATION_TIMEOUT, IPC_ENDPOINT_FILE_NAME, IPC_MAX_MESSAGE_SIZE,
+        IPC_MAX_UNAUTHENTICATED_MESSAGE_SIZE, IPC_PING_TIMEOUT, IPC_PROTOCOL_VERSION,
+        IPC_SOCKET_FILE_NAME, IPC_SUBSCRIBER_QUEUE_SIZE, IPC_SUBSCRIBER_WRITE_TIMEOUT,
+        IPC_TOKEN_FILE_NAME, SERVER_ACCEPT_INTERVAL, TCP_READ_TIMEOUT, TCP_SERVER_ADDR,
+    },
+    log_error, log_info, log_print, log_warn,
+    service::{
+        bus::{self, ConfigEvent, DeviceCommand, DeviceEvent, InteractionEvent, ServiceEvent},
+        interaction::{get_command_output, trigger_component_interaction, CommandOutput},
+        plugin::get_plugin_descriptions,
+        profile::switch_profile_by_name,
+        shutdown::{is_shutting_down, shutdown},
+        tray::open_dashboard,
     },
-    log_error, log_info, log_print,
-    utility::restart,
 };
 
 pub static SERVER_DATA: OnceLock<Arc<Mutex<ServerData>>> = OnceLock::new();
+static SUBSCRIBERS: OnceLock<Mutex<Vec<mpsc::SyncSender<ServerEvent>>>> = OnceLock::new();
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+static IPC_TOKEN: OnceLock<String> = OnceLock::new(); // Only set in the `Service`
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Every method with its current version, a request asking for a newer version is rejected
+const METHODS: &[(&str, u32)] = &[
+    ("version", 1),
+    ("authenticate", 1),
+    ("restart", 1),
+    ("reload_config", 1),
+    ("send_serial", 1),
+    ("command_output", 1),
+    ("plugins", 1),
+    ("status", 1),
+    ("profiles", 1),
+    ("switch_profile", 1),
+    ("trigger", 1),
+    ("config_export", 1),
+    ("config_import", 1),
+    ("subscribe", 1),
+    ("open_dashboard", 1),
+    ("simulate", 1),
+];
+
+pub enum EndpointOwner {
+    Nobody,
+    PadPad(String /* app version */),
+    OtherProgram,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ServerData {
-    #[serde(skip)]
-    pub last_data_string: String, // Last data that client received to compare if it needs update
     pub is_client_connected: bool, // Connection status between TCP `server` and `client`
     pub is_device_paired: bool,    // Connection status between `device` and `software`
     pub firmware_version: String,
     pub raw_layout: (String /* buttons */, String /* potentiometers */),
-    pub last_updated_component: (
-        String, /* component_global_id */
-        String, /* value */
-    ), // To send the component's value, that was just changed to the client
-    pub order: String, // Server order message for client to do something. e.g. Reload config
-    pub pending_serial_message: String, // Requested message by the client to be sent via serial
-}
-
-impl ServerData {
-    pub fn to_string(&self) -> String {
-        serde_json::to_string(self).unwrap_or("{}".to_string())
-    }
-
-    pub fn parse(server_data_string: String) -> Self {
-        serde_json::from_str(&server_data_string).unwrap_or(Self::default())
-    }
 }
 
 impl Default for ServerData {
     fn default() -> Self {
         Self {
-            last_data_string: String::new(),
             is_client_connected: false,
             is_device_paired: false,
             firmware_version: String::new(),
             raw_layout: (String::new(), String::new()),
-            last_updated_component: (String::new(), String::new()),
-            order: String::new(),
-            pending_serial_message: String::new(),
         }
     }
 }
 
-pub fn is_another_instance_running() -> bool {
-    let mut another_instance_running = false;
+/// Events that are streamed to subscribed `TCP clients` in the order they happened
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ServerEvent {
+    State(ServerData), // Sent on subscribe and whenever pairing, firmware or layout changes
+    Component(
+        String, /* component_global_id */
+        String, /* value */
+    ),
+    Profile(usize /* index */, String /* name */),
+    ReloadConfig(),
+    CommandOutput(String /* component_global_id */, CommandOutput), // A command has finished
+}
+
+/// A JSON-RPC 2.0 request, messages are separated by a newline
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>, // Notifications don't have it and don't get a response
+    method: String,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    version: Option<u32>, // Version of the method the client expects, latest if it's not set
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IpcError {
+    Connection(String), // Server couldn't be reached or the connection was lost
+    Parse(String),
+    InvalidRequest(String),
+    MethodNotFound(String),
+    InvalidParams(String),
+    UnsupportedVersion(String),
+    Unauthorized(String),
+    Internal(String),
+}
+
+impl IpcError {
+    pub fn code(&self) -> i64 {
+        match self {
+            IpcError::Connection(_) => -32099,
+            IpcError::Parse(_) => -32700,
+            IpcError::InvalidRequest(_) => -32600,
+            IpcError::MethodNotFound(_) => -32601,
+            IpcError::InvalidParams(_) => -32602,
+            IpcError::UnsupportedVersion(_) => -32000,
+            IpcError::Unauthorized(_) => -32001,
+            IpcError::Internal(_) => -32603,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            IpcError::Connection(m)
+            | IpcError::Parse(m)
+            | IpcError::InvalidRequest(m)
+            | IpcError::MethodNotFound(m)
+            | IpcError::InvalidParams(m)
+            | IpcError::UnsupportedVersion(m)
+            | IpcError::Unauthorized(m)
+            | IpcError::Internal(m) => m,
+        }
+    }
+
+    fn from_error_object(error: &Value) -> Self {
+        let message = error["message"].as_str().unwrap_or_default().to_string();
+
+        match error["code"].as_i64().unwrap_or(0) {
+            -32700 => IpcError::Parse(message),
+            -32600 => IpcError::InvalidRequest(message),
+            -32601 => IpcError::MethodNotFound(message),
+            -32602 => IpcError::InvalidParams(message),
+            -32000 => IpcError::UnsupportedVersion(message),
+            -32001 => IpcError::Unauthorized(message),
+            _ => IpcError::Internal(message),
+        }
+    }
+
+    fn to_error_object(&self) -> Value {
+        json!({ "code": self.code(), "message": self.message() })
+    }
+}
+
+impl std::fmt::Display for IpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+/// Where the `Service` listens, clients find it in the endpoint file next to the config
+#[derive(Debug, Clone, PartialEq)]
+pub enum IpcEndpoint {
+    Tcp(String /* address */),
+    Unix(PathBuf /* socket path */),
+}
+
+impl IpcEndpoint {
+    pub fn parse(endpoint: &str) -> Option<Self> {
+        match endpoint.trim().split_once(':') {
+            Some(("tcp", address)) => Some(IpcEndpoint::Tcp(address.to_string())),
+            Some(("unix", path)) => Some(IpcEndpoint::Unix(PathBuf::from(path))),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for IpcEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpcEndpoint::Tcp(address) => write!(f, "tcp:{}", address),
+            IpcEndpoint::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+enum IpcListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl IpcListener {
+    fn bind(endpoint: &IpcEndpoint) -> std::io::Result<Self> {
+        match endpoint {
+            IpcEndpoint::Tcp(address) => TcpListener::bind(address).map(IpcListener::Tcp),
+            #[cfg(unix)]
+            IpcEndpoint::Unix(path) => {
+                use std::os::unix::fs::PermissionsExt;
+
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+
+                // A socket file that's left from a crash would make `bind` fail
+                std::fs::remove_file(path).ok();
+
+                let listener = std::os::unix::net::UnixListener::bind(path)?;
+
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+                Ok(IpcListener::Unix(listener))
+            }
+            #[cfg(not(unix))]
+            IpcEndpoint::Unix(_) => Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "Unix sockets aren't supported on this platform!",
+            )),
+        }
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        match self {
+            IpcListener::Tcp(listener) => listener.set_nonblocking(nonblocking),
+            #[cfg(unix)]
+            IpcListener::Unix(listener) => listener.set_nonblocking(nonblocking),
+        }
+    }
+
+    fn accept(&self) -> std::io::Result<IpcStream> {
+        match self {
+            IpcListener::Tcp(listener) => listener.accept().map(|(s, _)| IpcStream::Tcp(s)),
+            #[cfg(unix)]
+            IpcListener::Unix(listener) => listener.accept().map(|(s, _)| IpcStream::Unix(s)),
+        }
+    }
+}
+
+enum IpcStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixStream),
+}
+
+impl IpcStream {
+    fn connect(endpoint: &IpcEndpoint) -> std::io::Result<Self> {
+        match endpoint {
+            IpcEndpoint::Tcp(address) => TcpStream::connect(address).map(IpcStream::Tcp),
+            #[cfg(unix)]
+            IpcEndpoint::Unix(path) => {
+                std::os::unix::net::UnixStream::connect(path).map(IpcStream::Unix)
+            }
+            #[cfg(not(unix))]
+            IpcEndpoint::Unix(_) => Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "Unix sockets aren't supported on this platform!",
+            )),
+        }
+    }
+
+    fn try_clone(&self) -> std::io::Result<Self> {
+        match self {
+            IpcStream::Tcp(stream) => stream.try_clone().map(IpcStream::Tcp),
+            #[cfg(unix)]
+            IpcStream::Unix(stream) => stream.try_clone().map(IpcStream::Unix),
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Option<std::time::Duration>) -> std::io::Result<()> {
+        match self {
+            IpcStream::Tcp(stream) => stream.set_read_timeout(timeout),
+            #[cfg(unix)]
+            IpcStream::Unix(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        match self {
+            IpcStream::Tcp(stream) => stream.set_nonblocking(nonblocking),
+            #[cfg(unix)]
+            IpcStream::Unix(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+
+    fn set_write_timeout(&self, timeout: Option<std::time::Duration>) -> std::io::Result<()> {
+        match self {
+            IpcStream::Tcp(stream) => stream.set_write_timeout(timeout),
+            #[cfg(unix)]
+            IpcStream::Unix(stream) => stream.set_write_timeout(timeout),
+        }
+    }
+}
+
+/// Streams whose reads can time out, see `DeadlineReader`
+pub trait SetReadTimeout {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()>;
+}
+
+impl SetReadTimeout for IpcStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        IpcStream::set_read_timeout(self, timeout)
+    }
+}
+
+impl SetReadTimeout for TcpStream {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+impl<T: SetReadTimeout> SetReadTimeout for &T {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        (*self).set_read_timeout(timeout)
+    }
+}
+
+/// Every read only waits for what's left until `deadline`, so a client can't stay connected
+/// by sending a byte at a time. `None` waits as long as the stream's own timeout
+pub struct DeadlineReader<S> {
+    pub stream: S,
+    pub deadline: Option<Instant>,
+}
+
+impl<S: Read + SetReadTimeout> Read for DeadlineReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(deadline) = self.deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            if remaining.is_zero() {
+                return Err(std::io::Error::new(
+                    ErrorKind::TimedOut,
+                    "Deadline has passed",
+                ));
+            }
+
+            self.stream.set_read_timeout(Some(remaining))?;
+        }
+
+        self.stream.read(buf)
+    }
+}
+
+impl Read for IpcStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            IpcStream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            IpcStream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for IpcStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            IpcStream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            IpcStream::Unix(stream) => stream.write(buf),
+        }
+    }
 
-    match TcpListener::bind(TCP_SERVER_ADDR) {
-        Err(e) if e.kind() == ErrorKind::AddrInUse => another_instance_running = true,
-        _ => (),
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            IpcStream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            IpcStream::Unix(stream) => stream.flush(),
+        }
     }
+}
 
-    another_instance_running
+/// `version` doesn't need the IPC token, so it tells a PadPad `Service` apart from other programs
+pub fn identify_endpoint_owner(endpoint: &IpcEndpoint) -> EndpointOwner {
+    // The socket file stays after a crash, so only a listening socket counts
+    let stream = match IpcStream::connect(endpoint) {
+        Ok(s) => s,
+        Err(_) => return EndpointOwner::Nobody,
+    };
+
+    stream
+        .set_read_timeout(Some(std::time::Duration::from_millis(IPC_PING_TIMEOUT)))
+        .ok();
+
+    let mut connection = BufReader::new(stream);
+
+    match send_request(&mut connection, "version", Value::Null) {
+        Ok(version) if version["protocol"].is_u64() => {
+            EndpointOwner::PadPad(version["app"].as_str().unwrap_or("unknown").to_string())
+        }
+        _ => EndpointOwner::OtherProgram,
+    }
+}
+
+/// Read the IPC token or create it on the first run, only the current user can read it
+pub fn init_ipc_token() {
+    let path = get_ipc_file_path(IPC_TOKEN_FILE_NAME);
+
+    let token = match read_ipc_token() {
+        Some(t) => t,
+        None => {
+            let token = generate_ipc_token();
+
+            if let Err(e) = write_ipc_token(&path, &token) {
+                log_error!(
+                    "Could not save the IPC token to `{}`: {}",
+                    path.display(),
+                    e
+                );
+            } else {
+                log_info!("A new IPC token was created at `{}`", path.display());
+            }
+
+            token
+        }
+    };
+
+    // Make sure a token that was copied or created by hand isn't readable by other users
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).ok();
+    }
+
+    IPC_TOKEN.set(token).ok();
 }
 
 pub fn handle_tcp_server() {
-    let listener =
-        TcpListener::bind(TCP_SERVER_ADDR).expect("TCP server could not bind to address!");
+    let endpoint = get_server_endpoint();
 
-    log_info!("TCP server is running on {}", TCP_SERVER_ADDR);
+    let listener = IpcListener::bind(&endpoint).expect("IPC server could not bind to address!");
 
-    for stream in listener.incoming() {
-        match stream {
-            Ok(mut stream) => {
+    // Clients read the endpoint from this file
+    let endpoint_path = get_ipc_file_path(IPC_ENDPOINT_FILE_NAME);
+
+    if let Err(e) = std::fs::write(&endpoint_path, endpoint.to_string()) {
+        log_error!(
+            "Could not save the IPC endpoint to `{}`: {}",
+            endpoint_path.display(),
+            e
+        );
+    }
+
+    log_info!("IPC server is running on {}", endpoint);
+
+    // Doesn't block, so the server stops once the app is shutting down
+    listener
+        .set_nonblocking(true)
+        .expect("IPC server could not be set to non-blocking!");
+
+    while !is_shutting_down() {
+        match listener.accept() {
+            Ok(stream) => {
                 if DEBUG_TCP_CLIENT_CONNECTION {
                     log_print!("TCP new connection established.");
                 }
 
-                std::thread::spawn(move || {
-                    // Handle clients
-                    let mut buffer = vec![0; TCP_BUFFER_SIZE];
-
-                    loop {
-                        match stream.read(&mut buffer) {
-                            Ok(0) => {
-                                if DEBUG_TCP_CLIENT_CONNECTION {
-                                    log_print!("Client disconnected.");
-                                }
-
-                                break;
-                            }
-                            // Windows handles this differently
-                            Err(e)
-                                if e.kind() == ErrorKind::ConnectionReset
-                                    || e.kind() == ErrorKind::BrokenPipe =>
-                            {
-                                if DEBUG_TCP_CLIENT_CONNECTION {
-                                    log_print!("Client disconnected.");
-                                }
-
-                                break;
-                            }
-                            Ok(bytes_read) => {
-                                let message = String::from_utf8_lossy(&buffer[..bytes_read]);
-
-                                server_to_client_message(&mut stream, message.trim());
-                            }
-                            Err(e) => {
-                                log_error!(
-                                    "TCP server had an error while reading from stream: {}",
-                                    e
-                                );
-
-                                break;
-                            }
-                        }
+                stream.set_nonblocking(false).ok();
 
-                        std::thread::sleep(std::time::Duration::from_millis(10));
-                    }
-                });
+                std::thread::spawn(move || handle_tcp_connection(stream));
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(SERVER_ACCEPT_INTERVAL));
             }
             Err(e) => {
                 log_error!("TCP connection failed: {}", e);
@@ -133,39 +481,180 @@ pub fn handle_tcp_server() {
     }
 }
 
-fn handle_tcp_client() -> Result<TcpStream, String> {
-    let stream = match std::net::TcpStream::connect(TCP_SERVER_ADDR) {
-        Ok(s) => s,
-        Err(_) => {
-            return Err(
-                "Failed to connect to server!\nMake sure the `Service` app is running!".into(),
-            )
+fn handle_tcp_connection(mut stream: IpcStream) {
+    // Clients that don't authenticate in time are disconnected, the deadline is removed after it
+    let mut reader = match stream.try_clone() {
+        Ok(s) => BufReader::new(DeadlineReader {
+            stream: s,
+            deadline: Some(Instant::now() + Duration::from_millis(IPC_AUTHENTICATION_TIMEOUT)),
+        }),
+        Err(e) => {
+            log_error!("TCP server could not read from stream: {}", e);
+
+            return;
         }
     };
 
-    stream
-        .set_read_timeout(Some(std::time::Duration::from_millis(TCP_READ_TIMEOUT)))
-        .expect("Failed to set `read_timeout`");
+    let mut is_authenticated = false;
 
-    Ok(stream)
-}
+    loop {
+        let max_message_size = if is_authenticated {
+            IPC_MAX_MESSAGE_SIZE
+        } else {
+            IPC_MAX_UNAUTHENTICATED_MESSAGE_SIZE
+        };
 
-fn server_to_client_message(client_stream: &mut TcpStream, message: &str) {
-    if DEBUG_TCP_SERVER_MESSAGE_CONFIRMATION {
-        log_print!("Received message: {}", message);
+        let mut line = String::new();
+
+        // Windows reports a reset/broken connection as an error instead of the end of stream
+        match reader
+            .by_ref()
+            .take(max_message_size as u64)
+            .read_line(&mut line)
+        {
+            Ok(0) => break,
+            Ok(size) if size >= max_message_size && !line.ends_with('\n') => {
+                log_error!("A TCP client sent a message that is too long!");
+
+                break;
+            }
+            Ok(_) => (),
+            Err(e)
+                if e.kind() == ErrorKind::ConnectionReset || e.kind() == ErrorKind::BrokenPipe =>
+            {
+                break;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                log_error!("A TCP client didn't authenticate in time!");
+
+                break;
+            }
+            Err(e) => {
+                log_error!("TCP server had an error while reading from stream: {}", e);
+
+                break;
+            }
+        }
+
+        let line = line.trim();
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if DEBUG_TCP_SERVER_MESSAGE_CONFIRMATION {
+            log_print!("Received message: {}", line);
+        }
+
+        let (id, result) = match serde_json::from_str::<Request>(line) {
+            Ok(request) => {
+                let result = match request.method.as_str() {
+                    "authenticate" => {
+                        is_authenticated = is_token_valid(request.params["token"].as_str());
+
+                        if is_authenticated {
+                            reader.get_mut().deadline = None;
+
+                            stream.set_read_timeout(None).ok();
+
+                            Ok(json!("ok"))
+                        } else {
+                            log_error!("A TCP client sent an invalid IPC token!");
+
+                            Err(IpcError::Unauthorized("Invalid token!".to_string()))
+                        }
+                    }
+                    // Clients can check the protocol before authenticating
+                    "version" => handle_request(&request),
+                    _ if !is_authenticated => Err(IpcError::Unauthorized(
+                        "Call `authenticate` with the IPC token first!".to_string(),
+                    )),
+                    _ => handle_request(&request),
+                };
+
+                // The connection is kept open and only used for events
+                if request.method == "subscribe" && result.is_ok() {
+                    let is_acknowledged = match &request.id {
+                        Some(id) => {
+                            write_message(&mut stream, &response_message(id, result)).is_ok()
+                        }
+                        None => true,
+                    };
+
+                    if is_acknowledged {
+                        handle_subscriber(stream);
+                    }
+
+                    return;
+                }
+
+                match request.id {
+                    Some(id) => (id, result),
+                    None => continue,
+                }
+            }
+            Err(e) => (Value::Null, Err(IpcError::Parse(e.to_string()))),
+        };
+
+        if write_message(&mut stream, &response_message(&id, result)).is_err() {
+            break;
+        }
     }
 
-    let mut response: Option<String> = None;
+    if DEBUG_TCP_CLIENT_CONNECTION {
+        log_print!("Client disconnected.");
+    }
+}
 
-    let (key, value) = message
-        .split_once(SERIAL_MESSAGE_SEP)
-        .unwrap_or((&message, ""));
+fn handle_request(request: &Request) -> Result<Value, IpcError> {
+    let method_version = match METHODS.iter().find(|(name, _)| *name == request.method) {
+        Some((_, version)) => *version,
+        None => {
+            return Err(IpcError::MethodNotFound(format!(
+                "Method `{}` doesn't exist!",
+                request.method
+            )))
+        }
+    };
 
-    match key {
+    if let Some(version) = request.version {
+        if version > method_version {
+            return Err(IpcError::UnsupportedVersion(format!(
+                "Method `{}` only supports version {} and lower, update the `Service` app!",
+                request.method, method_version
+            )));
+        }
+    }
+
+    let string_param = |name: &str| -> Result<String, IpcError> {
+        request.params[name]
+            .as_str()
+            .map(|value| value.to_string())
+            .ok_or(IpcError::InvalidParams(format!(
+                "Parameter `{}` is missing!",
+                name
+            )))
+    };
+
+    match request.method.as_str() {
+        "version" => Ok(json!({
+            "protocol": IPC_PROTOCOL_VERSION,
+            "app": APP_VERSION,
+            "methods": METHODS
+                .iter()
+                .map(|(name, version)| (name.to_string(), json!(version)))
+                .collect::<serde_json::Map<String, Value>>(),
+        })),
         "restart" => {
-            restart();
+            // Only asks the main thread, so the response is sent before the `Service` goes away
+            shutdown(true);
+
+            Ok(json!("ok"))
+        }
+        "open_dashboard" => {
+            open_dashboard();
 
-            response = Some("ok".to_string());
+            Ok(json!("ok"))
         }
         "reload_config" => {
             let mut config = CONFIG
@@ -176,102 +665,499 @@ fn server_to_client_message(client_stream: &mut TcpStream, message: &str) {
 
             config.load();
 
-            response = Some("ok".to_string());
+            Ok(json!("ok"))
         }
-        "handled" => {
-            if let Ok(mut server_data) = get_server_data().lock() {
-                server_data.order = String::new();
+        "send_serial" => {
+            let message = string_param("message")?;
 
-                let data_string = server_data.to_string();
+            // The `Dashboard` asks for a refresh by its name
+            bus::send_to_device(match message.as_str() {
+                "refresh_device" => DeviceCommand::Refresh(),
+                _ => DeviceCommand::Message(message),
+            });
 
-                response = Some("ok".to_string());
+            Ok(json!("ok"))
+        }
+        "command_output" => {
+            let output = get_command_output(&string_param("component")?).unwrap_or_default();
 
-                server_data.last_data_string = data_string;
-                server_data.last_updated_component = (String::new(), String::new())
-            }
+            serde_json::to_value(output).map_err(|e| IpcError::Internal(e.to_string()))
         }
-        "force_data" => {
-            if let Ok(mut server_data) = get_server_data().lock() {
-                let data_string = server_data.to_string();
+        "plugins" => serde_json::to_value(get_plugin_descriptions())
+            .map_err(|e| IpcError::Internal(e.to_string())),
+        "status" => {
+            let endpoint = get_server_endpoint();
 
-                response = Some(data_string.clone());
+            let server_data = get_server_data()
+                .lock()
+                .map(|data| data.clone())
+                .map_err(|e| IpcError::Internal(e.to_string()))?;
 
-                server_data.last_data_string = data_string;
-                server_data.last_updated_component = (String::new(), String::new())
+            let config = CONFIG
+                .get()
+                .expect("Could not retrieve CONFIG data!")
+                .lock()
+                .unwrap();
+
+            let current_profile = config.settings.current_profile;
+
+            Ok(json!({
+                "app": APP_VERSION,
+                "protocol": IPC_PROTOCOL_VERSION,
+                "endpoint": endpoint.to_string(),
+                "is_device_paired": server_data.is_device_paired,
+                "firmware_version": server_data.firmware_version,
+                "config_file": config.find_file_path(),
+                "profile": {
+                    "index": current_profile,
+                    "name": config.profiles.get(current_profile).map(|p| p.name.clone()),
+                },
+            }))
+        }
+        "profiles" => {
+            let config = CONFIG
+                .get()
+                .expect("Could not retrieve CONFIG data!")
+                .lock()
+                .unwrap();
+
+            Ok(config
+                .profiles
+                .iter()
+                .enumerate()
+                .map(|(index, profile)| {
+                    json!({
+                        "index": index,
+                        "name": profile.name,
+                        "current": index == config.settings.current_profile,
+                    })
+                })
+                .collect())
+        }
+        "switch_profile" => {
+            let profile = string_param("profile")?;
+
+            if !switch_profile_by_name(&profile) {
+                return Err(IpcError::InvalidParams(format!(
+                    "Profile `{}` doesn't exist!",
+                    profile
+                )));
             }
+
+            Ok(json!("ok"))
         }
-        "data" => {
-            if let Ok(mut server_data) = get_server_data().lock() {
-                let data_string = server_data.to_string();
-
-                if server_data.last_data_string.is_empty()
-                    || server_data.last_data_string != data_string
-                {
-                    response = Some(data_string.clone());
-                } else {
-                    // "0" means the data hasn't been changed since last ping
-                    response = Some("0".to_string());
+        "trigger" => {
+            let component_global_id = string_param("component")?;
+
+            // Numbers are accepted too, e.g. potentiometer values
+            let value = match &request.params["value"] {
+                Value::Null => None,
+                Value::String(value) => Some(value.clone()),
+                value => Some(value.to_string()),
+            };
+
+            let modkey = request.params["modkey"].as_bool().unwrap_or(false);
+            let confirm = request.params["confirm"].as_bool().unwrap_or(false);
+
+            trigger_component_interaction(&component_global_id, value, modkey, confirm)
+                .map_err(IpcError::InvalidParams)?;
+
+            Ok(json!("ok"))
+        }
+        "simulate" => {
+            let component_global_id = string_param("component")?;
+
+            let value = match &request.params["value"] {
+                Value::Null => {
+                    return Err(IpcError::InvalidParams(
+                        "Parameter `value` is missing!".to_string(),
+                    ))
                 }
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+
+            let modkey = request.params["modkey"].as_bool().unwrap_or(false);
 
-                server_data.last_data_string = data_string;
-                server_data.last_updated_component = (String::new(), String::new())
+            let (kind, id) = component_global_id
+                .split_once(':')
+                .and_then(|(kind, id)| Some((kind, id.parse::<u8>().ok().filter(|id| *id != 0)?)))
+                .ok_or(IpcError::InvalidParams(format!(
+                    "Invalid component `{}`, expected e.g. `Button:1`!",
+                    component_global_id
+                )))?;
+
+            // Only what the device can send is simulated
+            let (kind, is_valid_value) = match kind {
+                "Button" => (ComponentKind::Button, matches!(value.as_str(), "0" | "1")),
+                "Potentiometer" => (
+                    ComponentKind::Potentiometer,
+                    value.parse::<u8>().is_ok_and(|value| value <= 99),
+                ),
+                // Encoders are only drawn in the layout, the device doesn't send their steps and they
+                // can't have interactions, so there's nothing to dispatch yet
+                "RotaryEncoder" => {
+                    return Err(IpcError::InvalidParams(
+                        "`RotaryEncoder` steps can't be simulated yet, the device doesn't send encoder events and encoders can't have interactions!".to_string(),
+                    ))
+                }
+                _ => {
+                    return Err(IpcError::InvalidParams(format!(
+                        "`{}` events can't be simulated, the device only sends `Button` and `Potentiometer` events!",
+                        kind
+                    )))
+                }
+            };
+
+            if !is_valid_value {
+                return Err(IpcError::InvalidParams(format!(
+                    "Invalid value `{}`, buttons take `1` (pressed) or `0` (released) and potentiometers `0`-`99`!",
+                    value
+                )));
             }
+
+            log_info!(
+                "[SIMULATED] `{}` | modkey: `{}` | value: `{}`",
+                component_global_id,
+                modkey,
+                value
+            );
+
+            bus::publish(ServiceEvent::Device(DeviceEvent::Input(
+                kind, id, value, modkey,
+            )));
+
+            Ok(json!("ok"))
         }
-        "send_serial" => {
-            if let Ok(mut server_data) = get_server_data().lock() {
-                server_data.pending_serial_message = value.to_string();
+        "config_export" => {
+            let config = CONFIG
+                .get()
+                .expect("Could not retrieve CONFIG data!")
+                .lock()
+                .unwrap();
 
-                response = Some("ok".to_string());
-            }
+            toml::to_string_pretty(&*config)
+                .map(Value::String)
+                .map_err(|e| IpcError::Internal(e.to_string()))
         }
-        _ => response = Some(message.to_string()),
+        "config_import" => {
+            let mut imported = check_config(&string_param("config")?)
+                .map_err(|errors| IpcError::InvalidParams(errors.join("\n")))?;
+
+            let mut config = CONFIG
+                .get()
+                .expect("Could not retrieve CONFIG data!")
+                .lock()
+                .unwrap();
+
+            imported.file_path = config.file_path.clone();
+
+            update_config_and_client(&mut config, |c| *c = imported);
+
+            bus::send_to_device(DeviceCommand::Refresh());
+
+            Ok(json!("ok"))
+        }
+        "subscribe" => Ok(json!("ok")),
+        _ => Err(IpcError::MethodNotFound(format!(
+            "Method `{}` isn't handled!",
+            request.method
+        ))),
     }
+}
 
-    if let Some(r) = response {
-        if DEBUG_TCP_SERVER_MESSAGE_CONFIRMATION {
-            log_print!("Sending a message to client: {}", r);
+fn handle_subscriber(mut stream: IpcStream) {
+    // A client that stops reading would block this thread forever otherwise
+    stream
+        .set_write_timeout(Some(std::time::Duration::from_millis(
+            IPC_SUBSCRIBER_WRITE_TIMEOUT,
+        )))
+        .ok();
+
+    for event in subscribe_events() {
+        let message = json!({
+            "jsonrpc": "2.0",
+            "method": "event",
+            "params": event,
+        });
+
+        if write_message(&mut stream, &message).is_err() {
+            break;
         }
+    }
 
-        client_stream.write_all(r.as_bytes()).unwrap();
+    if DEBUG_TCP_CLIENT_CONNECTION {
+        log_print!("Subscriber disconnected.");
     }
 }
 
-pub fn client_to_server_message(message: &str) -> Result<String, String> {
-    let mut stream = match handle_tcp_client() {
+/// Connect to the `Service` and authenticate with the token of the current user
+fn handle_tcp_client() -> Result<BufReader<IpcStream>, IpcError> {
+    let token = read_ipc_token().ok_or(IpcError::Unauthorized(format!(
+        "Could not read the IPC token at `{}`!\nMake sure the `Service` app was started by the same user!",
+        get_ipc_file_path(IPC_TOKEN_FILE_NAME).display()
+    )))?;
+
+    let stream = match IpcStream::connect(&discover_endpoint()) {
         Ok(s) => s,
-        Err(e) => return Err(e),
+        Err(_) => {
+            return Err(IpcError::Connection(
+                "Failed to connect to server!\nMake sure the `Service` app is running!".into(),
+            ))
+        }
     };
 
-    if let Err(e) = stream.write_all(message.as_bytes()) {
+    stream
+        .set_read_timeout(Some(std::time::Duration::from_millis(TCP_READ_TIMEOUT)))
+        .expect("Failed to set `read_timeout`");
+
+    let mut connection = BufReader::new(stream);
+
+    send_request(&mut connection, "authenticate", json!({ "token": token }))?;
+
+    Ok(connection)
+}
+
+fn send_request(
+    connection: &mut BufReader<IpcStream>,
+    method: &str,
+    params: Value,
+) -> Result<Value, IpcError> {
+    let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": params,
+    });
+
+    if let Err(e) = write_message(connection.get_mut(), &request) {
         log_error!("Failed to write to server: {:?}", e);
 
-        return Err("Failed to send a message!\nMake sure the `Service` app is running!".into());
+        return Err(IpcError::Connection(
+            "Failed to send a message!\nMake sure the `Service` app is running!".into(),
+        ));
     }
 
-    let mut buffer = vec![0; TCP_BUFFER_SIZE];
+    read_response(connection, id)
+}
+
+fn response_message(id: &Value, result: Result<Value, IpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error.to_error_object() }),
+    }
+}
+
+fn write_message(stream: &mut IpcStream, message: &Value) -> std::io::Result<()> {
+    if DEBUG_TCP_SERVER_MESSAGE_CONFIRMATION {
+        log_print!("Sending a message: {}", message);
+    }
 
-    match stream.read(&mut buffer) {
+    writeln!(stream, "{}", message).and_then(|_| stream.flush())
+}
+
+fn read_response(reader: &mut BufReader<IpcStream>, id: u64) -> Result<Value, IpcError> {
+    let lost_connection = || {
+        IpcError::Connection(
+            "There was an `Unknown` problem while sending a message!\nMake sure the `Service` app is running!"
+                .into(),
+        )
+    };
+
+    let mut line = String::new();
+
+    match reader.read_line(&mut line) {
         Ok(0) => {
             log_print!("Server disconnected.");
+
+            return Err(lost_connection());
         }
+        Ok(_) => (),
         // Windows handles this differently
         Err(e) if e.kind() == ErrorKind::ConnectionReset || e.kind() == ErrorKind::BrokenPipe => {
             log_print!("Server disconnected.");
-        }
-        Ok(bytes_read) => {
-            let message = String::from_utf8_lossy(&buffer[..bytes_read]);
 
-            return Ok(message.trim().to_string());
+            return Err(lost_connection());
         }
         Err(e) => {
             let error = format!("TCP client had an error while reading from stream: {}", e);
             log_error!("{}", error);
 
-            return Err(error);
+            return Err(IpcError::Connection(error));
         }
     }
 
-    Err("There was an `Unknown` problem while sending a message!\nMake sure the `Service` app is running!".into())
+    let response: Value =
+        serde_json::from_str(&line).map_err(|e| IpcError::Parse(e.to_string()))?;
+
+    if response["id"] != id {
+        return Err(IpcError::InvalidRequest(format!(
+            "Expected a response to request `{}`, got `{}`",
+            id, response["id"]
+        )));
+    }
+
+    if !response["error"].is_null() {
+        return Err(IpcError::from_error_object(&response["error"]));
+    }
+
+    Ok(response["result"].clone())
+}
+
+/// Call a method of the `Service` and wait for its result
+pub fn client_to_server_message(method: &str, params: Value) -> Result<Value, IpcError> {
+    let mut connection = handle_tcp_client()?;
+
+    send_request(&mut connection, method, params)
+}
+
+/// Keep a connection open and call `on_event` for every event the server sends.
+/// Returns when the connection is lost
+pub fn subscribe_to_server<F>(mut on_event: F) -> Result<(), IpcError>
+where
+    F: FnMut(ServerEvent),
+{
+    let mut reader = handle_tcp_client()?;
+
+    send_request(&mut reader, "subscribe", Value::Null)?;
+
+    // Events can be minutes apart
+    reader.get_ref().set_read_timeout(None).ok();
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => break,
+        };
+
+        let event = serde_json::from_str::<Value>(&line)
+            .and_then(|message| serde_json::from_value::<ServerEvent>(message["params"].clone()));
+
+        match event {
+            Ok(event) => on_event(event),
+            Err(e) => log_error!("Received an invalid event from server: {}", e),
+        }
+    }
+
+    Err(IpcError::Connection(
+        "Lost connection to server!\nMake sure the `Service` app is running!".into(),
+    ))
+}
+
+/// Receive every event from now on, starting with the current `State`. The channel is closed
+/// when `IPC_SUBSCRIBER_QUEUE_SIZE` events are waiting, so a stalled subscriber can't pile them up
+pub fn subscribe_events() -> mpsc::Receiver<ServerEvent> {
+    let (sender, receiver) = mpsc::sync_channel(IPC_SUBSCRIBER_QUEUE_SIZE);
+
+    // `SERVER_DATA` stays locked until the subscriber is added, so no state change is missed
+    if let Ok(server_data) = get_server_data().lock() {
+        sender
+            .try_send(ServerEvent::State(server_data.clone()))
+            .ok();
+
+        // The channel is closed right away while shutting down
+        if let Ok(mut subscribers) = get_subscribers().lock() {
+            if !is_shutting_down() {
+                subscribers.push(sender);
+            }
+        }
+    }
+
+    receiver
+}
+
+/// Disconnect every subscriber, e.g. IPC clients and WebSockets
+pub fn close_subscribers() {
+    if let Ok(mut subscribers) = get_subscribers().lock() {
+        subscribers.clear();
+    }
+}
+
+/// Call an IPC method from inside the `Service`. e.g. by the HTTP API
+pub fn handle_local_request(method: &str, params: Value) -> Result<Value, IpcError> {
+    handle_request(&Request {
+        id: None,
+        method: method.to_string(),
+        params,
+        version: None,
+    })
+}
+
+/// Turn the `Service` events into `ServerEvent`s for the subscribed `TCP clients`
+pub fn handle_service_events_thread(events: mpsc::Receiver<ServiceEvent>) {
+    for event in events {
+        match event {
+            ServiceEvent::Device(DeviceEvent::Paired(paired)) => {
+                update_server_data(|data| &mut data.is_device_paired, paired)
+            }
+            ServiceEvent::Device(DeviceEvent::FirmwareVersion(version)) => {
+                update_server_data(|data| &mut data.firmware_version, version)
+            }
+            ServiceEvent::Device(DeviceEvent::RawButtons(buttons)) => {
+                update_server_data(|data| &mut data.raw_layout.0, buttons)
+            }
+            ServiceEvent::Device(DeviceEvent::RawPotentiometers(potentiometers)) => {
+                update_server_data(|data| &mut data.raw_layout.1, potentiometers)
+            }
+            // Clients get the component's value once its interaction has handled it
+            ServiceEvent::Device(DeviceEvent::Input(..)) => (),
+            ServiceEvent::Config(ConfigEvent::Changed()) => {
+                broadcast_event(ServerEvent::ReloadConfig())
+            }
+            ServiceEvent::Config(ConfigEvent::ProfileSwitched(index, name)) => {
+                broadcast_event(ServerEvent::Profile(index, name))
+            }
+            ServiceEvent::Interaction(InteractionEvent::ComponentValue(
+                component_global_id,
+                value,
+            )) => broadcast_event(ServerEvent::Component(component_global_id, value)),
+            ServiceEvent::Interaction(InteractionEvent::CommandFinished(
+                component_global_id,
+                output,
+            )) => broadcast_event(ServerEvent::CommandOutput(component_global_id, output)),
+        }
+    }
+}
+
+/// Send an event to every subscribed `TCP client`
+fn broadcast_event(event: ServerEvent) {
+    if let Ok(mut subscribers) = get_subscribers().lock() {
+        subscribers.retain(|subscriber| match subscriber.try_send(event.clone()) {
+            Ok(_) => true,
+            Err(mpsc::TrySendError::Full(_)) => {
+                log_warn!("A subscriber isn't reading its events, disconnecting it...");
+
+                false
+            }
+            Err(mpsc::TrySendError::Disconnected(_)) => false,
+        });
+    }
+}
+
+/// Change a field of `SERVER_DATA` and let the subscribers know if it's a new value
+fn update_server_data<T, F>(field: F, value: T)
+where
+    T: PartialEq,
+    F: FnOnce(&mut ServerData) -> &mut T,
+{
+    let server_data = get_server_data();
+
+    let mut data = match server_data.lock() {
+        Ok(d) => d,
+        Err(_) => return,
+    };
+
+    let field = field(&mut data);
+
+    if *field == value {
+        return;
+    }
+
+    *field = value;
+
+    broadcast_event(ServerEvent::State(data.clone()));
 }
 
 pub fn get_server_data() -> Arc<Mutex<ServerData>> {
@@ -279,3 +1165,250 @@ pub fn get_server_data() -> Arc<Mutex<ServerData>> {
         .get_or_init(|| Arc::new(Mutex::new(ServerData::default())))
         .clone()
 }
+
+fn get_subscribers() -> &'static Mutex<Vec<mpsc::SyncSender<ServerEvent>>> {
+    SUBSCRIBERS.get_or_init(|| Mutex::new(vec![]))
+}
+
+pub fn is_token_valid(token: Option<&str>) -> bool {
+    match (IPC_TOKEN.get(), token) {
+        (Some(expected), Some(token)) => is_secret_equal(expected, token),
+        _ => false,
+    }
+}
+
+/// Compare every byte, so the time it takes doesn't tell how much of the secret was right
+pub fn is_secret_equal(expected: &str, secret: &str) -> bool {
+    if expected.len() != secret.len() {
+        return false;
+    }
+
+    expected
+        .bytes()
+        .zip(secret.bytes())
+        .fold(0, |difference, (a, b)| difference | (a ^ b))
+        == 0
+}
+
+fn generate_ipc_token() -> String {
+    let mut bytes = [0u8; 32];
+
+    #[cfg(unix)]
+    let filled = std::fs::File::open("/dev/urandom")
+        .and_then(|mut file| std::io::Read::read_exact(&mut file, &mut bytes))
+        .is_ok();
+    #[cfg(not(unix))]
+    let filled = false;
+
+    // Windows: `RandomState` is seeded by the OS's random number generator
+    if !filled {
+        use std::hash::{BuildHasher, Hasher};
+
+        for chunk in bytes.chunks_mut(8) {
+            let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+
+            hasher.write_u128(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or_default(),
+            );
+
+            chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+        }
+    }
+
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn write_ipc_token(path: &Path, token: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut options = std::fs::File::options();
+
+    options.write(true).create(true).truncate(true);
+
+    // Windows: files in the local app data folder are only accessible by the current user
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+
+    options.open(path)?.write_all(token.as_bytes())
+}
+
+fn read_ipc_token() -> Option<String> {
+    std::fs::read_to_string(get_ipc_file_path(IPC_TOKEN_FILE_NAME))
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Per-user Unix socket when `$XDG_RUNTIME_DIR` is available, otherwise the configured TCP address
+pub fn get_server_endpoint() -> IpcEndpoint {
+    let settings = CONFIG
+        .get()
+        .expect("Could not retrieve CONFIG data!")
+        .lock()
+        .unwrap()
+        .settings
+        .ipc
+        .clone();
+
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) if cfg!(unix) && settings.unix_socket => {
+            IpcEndpoint::Unix(PathBuf::from(runtime_dir).join(IPC_SOCKET_FILE_NAME))
+        }
+        _ => IpcEndpoint::Tcp(settings.tcp_address),
+    }
+}
+
+/// Endpoint that the `Service` saved when it started, or the default TCP address
+pub fn discover_endpoint() -> IpcEndpoint {
+    std::fs::read_to_string(get_ipc_file_path(IPC_ENDPOINT_FILE_NAME))
+        .ok()
+        .and_then(|endpoint| IpcEndpoint::parse(&endpoint))
+        .unwrap_or(IpcEndpoint::Tcp(TCP_SERVER_ADDR.to_string()))
+}
+
+// IPC files are kept next to the config file
+pub fn get_ipc_file_path(file_name: &str) -> PathBuf {
+    Path::new(&Config::default().file_path)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requests() {
+        let request: Request = serde_json::from_str(
+            r#"{"jsonrpc": "2.0", "id": 7, "method": "status", "version": 2}"#,
+        )
+        .unwrap();
+
+        assert_eq!(request.id, Some(json!(7)));
+        assert_eq!(request.method, "status");
+        assert_eq!(request.params, Value::Null);
+        assert_eq!(request.version, Some(2));
+
+        // Requests without an `id` (or a null one) are notifications
+        for line in [
+            r#"{"jsonrpc": "2.0", "method": "reload"}"#,
+            r#"{"jsonrpc": "2.0", "id": null, "method": "reload"}"#,
+        ] {
+            assert_eq!(serde_json::from_str::<Request>(line).unwrap().id, None);
+        }
+
+        assert!(serde_json::from_str::<Request>(r#"{"id": 1}"#).is_err());
+    }
+
+    #[test]
+    fn builds_responses() {
+        assert_eq!(
+            response_message(&json!(3), Ok(json!("ok"))),
+            json!({ "jsonrpc": "2.0", "id": 3, "result": "ok" })
+        );
+        assert_eq!(
+            response_message(&Value::Null, Err(IpcError::Parse("Bad JSON".to_string()))),
+            json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32700, "message": "Bad JSON" }
+            })
+        );
+    }
+
+    #[test]
+    fn round_trips_error_codes() {
+        let errors = [
+            (IpcError::Parse("a".to_string()), -32700),
+            (IpcError::InvalidRequest("b".to_string()), -32600),
+            (IpcError::MethodNotFound("c".to_string()), -32601),
+            (IpcError::InvalidParams("d".to_string()), -32602),
+            (IpcError::UnsupportedVersion("e".to_string()), -32000),
+            (IpcError::Unauthorized("f".to_string()), -32001),
+            (IpcError::Internal("g".to_string()), -32603),
+        ];
+
+        for (error, code) in errors {
+            assert_eq!(error.code(), code);
+            assert_eq!(IpcError::from_error_object(&error.to_error_object()), error);
+        }
+
+        // Connection errors only happen on the client, the server never sends them
+        let error = IpcError::Connection("h".to_string());
+
+        assert_eq!(error.code(), -32099);
+        assert_eq!(
+            IpcError::from_error_object(&error.to_error_object()),
+            IpcError::Internal("h".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_methods_and_versions() {
+        let request = |line: &str| serde_json::from_str::<Request>(line).unwrap();
+
+        assert_eq!(
+            handle_request(&request(r#"{"id": 1, "method": "missing"}"#))
+                .unwrap_err()
+                .code(),
+            -32601
+        );
+        assert_eq!(
+            handle_request(&request(
+                r#"{"id": 1, "method": "version", "version": 999}"#
+            ))
+            .unwrap_err()
+            .code(),
+            -32000
+        );
+    }
+
+    #[test]
+    fn deadline_covers_the_whole_line() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // Sends a byte at a time, always faster than a single read would time out
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+
+            for _ in 0..20 {
+                if stream.write_all(b"a").is_err() {
+                    break;
+                }
+
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        });
+
+        let (stream, _) = listener.accept().unwrap();
+        let started_at = Instant::now();
+
+        let mut reader = BufReader::new(DeadlineReader {
+            stream,
+            deadline: Some(started_at + Duration::from_millis(300)),
+        });
+
+        let error = reader.read_line(&mut String::new()).unwrap_err();
+
+        // The stream's own timeout is reported as `WouldBlock` on Unix
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::TimedOut | ErrorKind::WouldBlock
+        ));
+        assert!(started_at.elapsed() < Duration::from_millis(600));
+
+        drop(reader);
+        client.join().unwrap();
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{constants::INSTANCE_RESTART_ENV_VAR, log::flush_logs, log_error};
+
 // Function will look for the hex bytes inside a string that at least has braces "{", "}"
 // Return Vec<u8> of all hex bytes
 // e.g. `input` = { 0x00, 0xFE, 0x15, ..., 0xFF } => [0, 254, 21, ..., 255]
@@ -83,11 +85,34 @@ pub fn hex_bytes_string_to_vec(input: &str) -> Result<Vec<u8>, String> {
 
 pub fn restart() {
     let args: Vec<String> = std::env::args().collect();
-    std::process::Command::new(&args[0])
-        .args(&args[1..])
-        .spawn()
-        .expect("Failed to restart");
-    std::process::exit(0);
+    let mut command = std::process::Command::new(&args[0]);
+
+    command.args(&args[1..]).env(INSTANCE_RESTART_ENV_VAR, "1"); // Waits for this process to release the instance lock
+
+    // Replaces this process in place, so there's never two of them at the same time
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+
+        let error = command.exec(); // Only returns if it failed
+
+        log_error!(
+            "Could not restart in place: {}, starting a new process instead...",
+            error
+        );
+    }
+
+    // Everything is already stopped, so this process exits even if the new one didn't start
+    match command.spawn() {
+        Ok(_) => std::process::exit(0),
+        Err(e) => {
+            log_error!("Failed to restart: {}", e);
+
+            flush_logs();
+
+            std::process::exit(1);
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]